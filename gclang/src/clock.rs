@@ -0,0 +1,275 @@
+//! Time as seen by gclang programs. The host advances it with [`Scopes::advance_clock`],
+//! so timers follow game time instead of the frame rate and can be driven by hand.
+//!
+//! [`Scopes::advance_clock`]: crate::Scopes::advance_clock
+use super::executor::Function;
+use std::time::Duration;
+
+/// Most timer callbacks one [`Scopes::advance_clock`] call runs, the rest are left for the next one.
+///
+/// [`Scopes::advance_clock`]: crate::Scopes::advance_clock
+pub(crate) const MAX_TICKS: usize = 1000;
+
+#[derive(Default)]
+pub struct Clock {
+    uptime: Duration,
+    epoch: Duration,
+    timers: Vec<Timer>,
+    next_id: i32,
+}
+
+struct Timer {
+    id: i32,
+    due: Duration,
+    interval: Option<Duration>,
+    callback: Function,
+}
+
+impl Clock {
+    /// Time the clock has been advanced by.
+    pub fn uptime(&self) -> Duration {
+        self.uptime
+    }
+
+    /// Time since the Unix epoch, if the host set one with [`Clock::set_epoch`].
+    pub fn now(&self) -> Duration {
+        self.epoch + self.uptime
+    }
+
+    /// Sets the time since the Unix epoch at the current uptime.
+    pub fn set_epoch(&mut self, epoch: Duration) {
+        self.epoch = epoch.saturating_sub(self.uptime);
+    }
+
+    /// Calls `callback` after `delay`, and then every `interval` if there is one.
+    /// Returns the timer's id for [`Clock::cancel`].
+    pub fn schedule(
+        &mut self,
+        delay: Duration,
+        interval: Option<Duration>,
+        callback: Function,
+    ) -> i32 {
+        self.next_id += 1;
+        self.timers.push(Timer {
+            id: self.next_id,
+            due: self.uptime + delay,
+            // * A zero interval would fire forever without the clock moving
+            interval: interval.map(|interval| interval.max(Duration::from_millis(1))),
+            callback,
+        });
+        self.next_id
+    }
+
+    pub fn cancel(&mut self, id: i32) -> bool {
+        let count = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != count
+    }
+
+    /// Cancels every timer, e.g. when the programs that set them are gone.
+    pub fn cancel_all(&mut self) {
+        self.timers.clear();
+    }
+
+    /// Time until the next timer is due, `None` if there are no timers.
+    pub fn until_next_timer(&self) -> Option<Duration> {
+        self.timers
+            .iter()
+            .map(|timer| timer.due.saturating_sub(self.uptime))
+            .min()
+    }
+
+    /// Takes the earliest timer due until `time` and moves the clock to it, returning its id and callback.
+    /// Repeating timers are scheduled again before they're returned, so they can cancel themselves.
+    pub(crate) fn next_due(&mut self, time: Duration) -> Option<(i32, Function)> {
+        let (index, _) = self
+            .timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.due <= time)
+            .min_by_key(|(_, timer)| (timer.due, timer.id))?;
+        self.uptime = self.uptime.max(self.timers[index].due);
+        let timer = &mut self.timers[index];
+        match timer.interval {
+            Some(interval) => {
+                timer.due += interval;
+                Some((timer.id, timer.callback.clone()))
+            }
+            None => {
+                let timer = self.timers.remove(index);
+                Some((timer.id, timer.callback))
+            }
+        }
+    }
+
+    pub(crate) fn set_uptime(&mut self, uptime: Duration) {
+        self.uptime = self.uptime.max(uptime);
+    }
+}
+
+/// `time` since the Unix epoch as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub fn format_date(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    // * Days to a civil date, from Howard Hinnant's `civil_from_days`
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAX_TICKS;
+    use crate::{Library, Program, Scopes, Value};
+    use std::time::Duration;
+
+    fn run(scopes: &mut Scopes, library: &mut Library, code: &str) {
+        let result = Program::parse(code).unwrap().eval(scopes, library);
+        assert!(result.is_ok(), "{}", code);
+    }
+
+    fn advance(scopes: &mut Scopes, library: &mut Library, millis: u64) {
+        let result = scopes.advance_clock(library, Duration::from_millis(millis));
+        assert!(result.is_ok());
+    }
+
+    fn log(scopes: &Scopes) -> Vec<String> {
+        match scopes.get_global("log") {
+            Some(Value::Array(log)) => log.iter().map(ToString::to_string).collect(),
+            value => panic!("Expected the log, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn after_fires_once_in_due_order() {
+        let (mut scopes, mut library) = (Scopes::default(), Library::with_std());
+        run(
+            &mut scopes,
+            &mut library,
+            r#"
+                global log = [];
+                after(30, fn () { log = push(log, "b"); });
+                after(10, fn () { log = push(log, "a"); });
+                after(30, fn () { log = push(log, "c"); });
+            "#,
+        );
+        advance(&mut scopes, &mut library, 20);
+        assert_eq!(log(&scopes), ["a"]);
+        advance(&mut scopes, &mut library, 10);
+        assert_eq!(log(&scopes), ["a", "b", "c"]);
+        advance(&mut scopes, &mut library, 100);
+        assert_eq!(log(&scopes), ["a", "b", "c"]);
+        assert_eq!(scopes.clock().until_next_timer(), None);
+        assert_eq!(scopes.clock().uptime(), Duration::from_millis(130));
+    }
+
+    #[test]
+    fn every_rearms_until_cancelled() {
+        let (mut scopes, mut library) = (Scopes::default(), Library::with_std());
+        run(
+            &mut scopes,
+            &mut library,
+            r#"
+                global log = [];
+                global tick = every(10, fn () { log = push(log, uptime()); });
+                after(25, fn () { log = push(log, "after"); });
+            "#,
+        );
+        advance(&mut scopes, &mut library, 35);
+        assert_eq!(log(&scopes), ["10", "20", "after", "30"]);
+        assert_eq!(
+            scopes.clock().until_next_timer(),
+            Some(Duration::from_millis(5))
+        );
+        run(&mut scopes, &mut library, "cancel(tick);");
+        advance(&mut scopes, &mut library, 100);
+        assert_eq!(log(&scopes).len(), 4);
+        assert!(!scopes.clock_mut().cancel(1));
+    }
+
+    #[test]
+    fn timers_cancel_themselves_and_schedule_more() {
+        let (mut scopes, mut library) = (Scopes::default(), Library::with_std());
+        run(
+            &mut scopes,
+            &mut library,
+            r#"
+                global log = [];
+                global tick = 0;
+                tick = every(10, fn () {
+                    log = push(log, uptime());
+                    if len(log) == 2 {
+                        cancel(tick);
+                        after(5, fn () { log = push(log, "later"); });
+                    }
+                });
+            "#,
+        );
+        advance(&mut scopes, &mut library, 100);
+        assert_eq!(log(&scopes), ["10", "20", "later"]);
+    }
+
+    #[test]
+    fn cancel_all() {
+        let (mut scopes, mut library) = (Scopes::default(), Library::with_std());
+        run(
+            &mut scopes,
+            &mut library,
+            "global log = []; every(10, fn () { log = push(log, 1); }); after(5, fn () { log = push(log, 2); });",
+        );
+        scopes.clock_mut().cancel_all();
+        assert_eq!(scopes.clock().until_next_timer(), None);
+        advance(&mut scopes, &mut library, 100);
+        assert!(log(&scopes).is_empty());
+    }
+
+    #[test]
+    fn failing_timers_are_cancelled() {
+        let (mut scopes, mut library) = (Scopes::default(), Library::with_std());
+        run(
+            &mut scopes,
+            &mut library,
+            r#"
+                global log = [];
+                every(10, fn () { log = push(log, "tick"); remove([], 0); });
+                every(10, fn () { log = push(log, "tock"); });
+            "#,
+        );
+        let result = scopes.advance_clock(&mut library, Duration::from_millis(10));
+        assert!(result.is_err());
+        assert_eq!(log(&scopes), ["tick"]);
+        advance(&mut scopes, &mut library, 20);
+        assert_eq!(log(&scopes), ["tick", "tock", "tock", "tock"]);
+    }
+
+    #[test]
+    fn ticks_per_advance_are_capped() {
+        let (mut scopes, mut library) = (Scopes::default(), Library::with_std());
+        run(
+            &mut scopes,
+            &mut library,
+            "global log = []; every(1, fn () { log = push(log, 1); });",
+        );
+        advance(&mut scopes, &mut library, 5000);
+        assert_eq!(log(&scopes).len(), MAX_TICKS);
+        assert_eq!(scopes.clock().uptime(), Duration::from_millis(5000));
+        advance(&mut scopes, &mut library, 0);
+        assert_eq!(log(&scopes).len(), MAX_TICKS * 2);
+    }
+}
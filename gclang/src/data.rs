@@ -0,0 +1,199 @@
+//! JSON and RON text for gclang values. Only data survives the trip:
+//! ints, bools, strings, arrays, tables and unit.
+use super::executor::{bail, Result, Value};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub fn to_json(value: &Value, pretty: bool) -> Result<String> {
+    let encode = Encode {
+        value,
+        string_keys: true,
+    };
+    let text = if pretty {
+        serde_json::to_string_pretty(&encode)
+    } else {
+        serde_json::to_string(&encode)
+    };
+    match text {
+        Ok(text) => Ok(text),
+        Err(error) => bail!(late effect "Failed to encode JSON! {}", error),
+    }
+}
+
+pub fn from_json(text: &str) -> Result<Value> {
+    match serde_json::from_str::<Decode>(text) {
+        Ok(Decode(value)) => Ok(value),
+        Err(error) => bail!(late effect "Failed to decode JSON! {}", error),
+    }
+}
+
+pub fn to_ron(value: &Value, pretty: bool) -> Result<String> {
+    let encode = Encode {
+        value,
+        string_keys: false,
+    };
+    let text = if pretty {
+        ron::ser::to_string_pretty(&encode, ron::ser::PrettyConfig::default())
+    } else {
+        ron::ser::to_string(&encode)
+    };
+    match text {
+        Ok(text) => Ok(text),
+        Err(error) => bail!(late effect "Failed to encode RON! {}", error),
+    }
+}
+
+pub fn from_ron(text: &str) -> Result<Value> {
+    match ron::from_str::<Decode>(text) {
+        Ok(Decode(value)) => Ok(value),
+        Err(error) => bail!(late effect "Failed to decode RON! {}", error),
+    }
+}
+
+// * ----------------------------------- Encoding ----------------------------------- * //
+struct Encode<'v> {
+    value: &'v Value,
+    /// JSON objects can only have string keys.
+    string_keys: bool,
+}
+
+impl<'v> Encode<'v> {
+    fn with(&self, value: &'v Value) -> Self {
+        Self {
+            value,
+            string_keys: self.string_keys,
+        }
+    }
+}
+
+impl Serialize for Encode<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.value {
+            Value::Int(value) => serializer.serialize_i32(*value),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::String(value) => serializer.serialize_str(value),
+            Value::Array(array) => {
+                serializer.collect_seq(array.iter().map(|value| self.with(value)))
+            }
+            Value::Table(table) => {
+                if self.string_keys {
+                    if let Some(key) = table.keys().find(|key| !matches!(key, Value::String(_))) {
+                        return Err(ser::Error::custom(format!(
+                            "Table keys have to be strings! Key: {}",
+                            key.repr()
+                        )));
+                    }
+                }
+                serializer.collect_map(
+                    table
+                        .iter()
+                        .map(|(key, value)| (self.with(key), self.with(value))),
+                )
+            }
+            Value::Unit => serializer.serialize_unit(),
+            Value::Any(value) => self.with(value).serialize(serializer),
+            Value::Function(_) | Value::Never => Err(ser::Error::custom(format!(
+                "Values of type {} can't be encoded!",
+                self.value.type_name()
+            ))),
+        }
+    }
+}
+
+// * ----------------------------------- Decoding ----------------------------------- * //
+struct Decode(Value);
+
+impl<'de> Deserialize<'de> for Decode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(DecodeVisitor).map(Decode)
+    }
+}
+
+struct DecodeVisitor;
+
+impl<'de> Visitor<'de> for DecodeVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an int, bool, string, array, table or unit")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> std::result::Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<Value, E> {
+        i32::try_from(value)
+            .map(Value::Int)
+            .map_err(|_| E::custom(format!("Int out of range! Value: {}", value)))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<Value, E> {
+        i32::try_from(value)
+            .map(Value::Int)
+            .map_err(|_| E::custom(format!("Int out of range! Value: {}", value)))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<Value, E> {
+        if value.fract() == 0.0 && value >= i32::MIN as f64 && value <= i32::MAX as f64 {
+            Ok(Value::Int(value as i32))
+        } else {
+            Err(E::custom(format!(
+                "Floats aren't supported! Value: {}",
+                value
+            )))
+        }
+    }
+
+    fn visit_char<E: de::Error>(self, value: char) -> std::result::Result<Value, E> {
+        Ok(Value::String(String::from(value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<Value, E> {
+        Ok(Value::String(value.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> std::result::Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_none<E: de::Error>(self) -> std::result::Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Value, D::Error> {
+        Decode::deserialize(deserializer).map(|Decode(value)| value)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Value, D::Error> {
+        Decode::deserialize(deserializer).map(|Decode(value)| value)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Value, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(Decode(value)) = seq.next_element()? {
+            array.push(value);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Value, A::Error> {
+        let mut table = BTreeMap::new();
+        while let Some((Decode(key), Decode(value))) = map.next_entry()? {
+            table.insert(key, value);
+        }
+        Ok(Value::Table(table))
+    }
+}
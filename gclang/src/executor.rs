@@ -1,12 +1,19 @@
 use crate::ensure_type;
 
+use super::clock::Clock;
 use super::parser::*;
-use anyhow::{anyhow, Context, Error};
+use super::vfs::Vfs;
+use anyhow::{Context, Error};
 use laps::ast::NonEmptySepList;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
 
 pub type Result<T> = std::result::Result<T, Exception>;
 
+/// Everything that can interrupt evaluation: errors, effects that were not handled yet,
+/// and the control flow of `return` and `resume`.
 pub enum Exception {
     Error(Error),
     Effect(Effect),
@@ -23,6 +30,14 @@ pub struct Effect {
 }
 
 impl Effect {
+    pub fn new(effect: &str, handler: &str, args: Vec<Value>) -> Self {
+        Self {
+            effect: effect.to_owned(),
+            handler: handler.to_owned(),
+            args,
+        }
+    }
+
     pub fn error(message: String) -> Self {
         Self {
             effect: String::from("exception"),
@@ -30,6 +45,28 @@ impl Effect {
             args: vec![Value::String(message)],
         }
     }
+
+    pub fn args(&self) -> &[Value] {
+        &self.args
+    }
+}
+
+impl Exception {
+    /// Message to show when the exception escapes a program, `None` for a top-level `return`.
+    pub fn message(&self) -> Option<String> {
+        match self {
+            Exception::Error(error) => Some(error.to_string()),
+            Exception::Effect(Effect {
+                effect, handler, ..
+            })
+            | Exception::EffectUnwind(effect, handler, _) => Some(format!(
+                "Unhandled effect '{}' (handler '{}')!",
+                effect, handler
+            )),
+            Exception::Resume(_) => Some(String::from("Internal error: Resume lost path!")),
+            Exception::Return(_) => None,
+        }
+    }
 }
 
 impl From<anyhow::Error> for Exception {
@@ -41,11 +78,11 @@ impl From<anyhow::Error> for Exception {
 #[macro_export]
 macro_rules! ensure {
     ($cond: expr $(,)?) => {
-        ensure!($expr, concat!("Ensure failed! Condition: ", stringify!($cond)));
+        $crate::ensure!($cond, concat!("Ensure failed! Condition: ", stringify!($cond)));
     };
     ($cond: expr, $fmt:expr$(, $($arg:tt)*)?) => {
         if !($cond) {
-            bail!($fmt$(, $($arg)*)?);
+            $crate::bail!($fmt$(, $($arg)*)?);
         }
     };
 }
@@ -53,10 +90,10 @@ macro_rules! ensure {
 #[macro_export]
 macro_rules! bail {
     ($fmt:expr$(, $($arg:tt)*)?) => {
-        return Err($crate::gclang::Exception::Error(anyhow!($fmt$(, $($arg)*)?)))
+        return Err($crate::Exception::Error($crate::anyhow!($fmt$(, $($arg)*)?)))
     };
     (late effect $fmt:expr$(, $($arg:tt)*)?) => {
-        return Err($crate::gclang::Exception::Effect($crate::gclang::Effect::error(format!($fmt$(, $($arg)*)?))));
+        return Err($crate::Exception::Effect($crate::Effect::error(format!($fmt$(, $($arg)*)?))))
     };
     (effect $scopes: ident, $library: ident, $fmt:expr$(, $($arg:tt)*)?) => {
         on_effect(
@@ -110,6 +147,7 @@ pub fn on_effect(effect: Effect, scopes: &mut Scopes, library: &mut Library) ->
 pub use bail;
 pub use ensure;
 
+/// A runtime value of a gclang program.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Value {
     Int(i32),
@@ -183,12 +221,74 @@ impl Value {
             _ => false,
         }
     }
+
+    /// Name of the value's type, as used in signatures.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "int",
+            Value::Bool(_) => "bool",
+            Value::String(_) => "String",
+            Value::Array(_) => "Array",
+            Value::Table(_) => "Table",
+            Value::Function(_) => "fn",
+            Value::Unit => "Unit",
+            Value::Never => "Never",
+            Value::Any(value) => value.type_name(),
+        }
+    }
+
+    /// Unambiguous one-line form for debugging, with quoted and escaped strings.
+    pub fn repr(&self) -> String {
+        match self {
+            Value::String(value) => {
+                let mut repr = String::with_capacity(value.len() + 2);
+                repr.push('"');
+                for char in value.chars() {
+                    match char {
+                        '"' => repr.push_str("\\\""),
+                        '\\' => repr.push_str("\\\\"),
+                        '\n' => repr.push_str("\\n"),
+                        '\t' => repr.push_str("\\t"),
+                        '\r' => repr.push_str("\\r"),
+                        char if char.is_control() => {
+                            repr.push_str(&format!("\\x{:02x}", char as u32))
+                        }
+                        char => repr.push(char),
+                    }
+                }
+                repr.push('"');
+                repr
+            }
+            Value::Array(value) => format!(
+                "[{}]",
+                value.iter().map(Value::repr).collect::<Vec<_>>().join(", ")
+            ),
+            Value::Table(value) if value.is_empty() => String::from("{}"),
+            Value::Table(value) => format!(
+                "{{ {} }}",
+                value
+                    .iter()
+                    .map(|(key, value)| format!("{} = {};", key.repr(), value.repr()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            Value::Function(function) => format!("fn/{}", function.arity()),
+            Value::Unit => String::from("unit"),
+            Value::Any(value) => value.repr(),
+            value => value.to_string(),
+        }
+    }
 }
 
+/// Variables of a running program. Globals persist between evaluations,
+/// the local stack only lives while a program is running.
 #[derive(Default)]
 pub struct Scopes {
     global: HashMap<String, Value>,
     local: Vec<StackFrame>,
+    rng: Option<StdRng>,
+    clock: Clock,
+    vfs: Vfs,
 }
 
 #[derive(Default)]
@@ -208,6 +308,20 @@ pub struct Function {
 }
 
 impl Function {
+    /// Calls the function from host code, checking arguments the same way a script call does.
+    pub fn call(
+        &self,
+        scopes: &mut Scopes,
+        library: &mut Library,
+        args: Vec<Value>,
+    ) -> Result<Value> {
+        self.eval(scopes, library, args, None)
+    }
+
+    pub fn arity(&self) -> usize {
+        self.args.len()
+    }
+
     fn eval(
         &self,
         scopes: &mut Scopes,
@@ -403,31 +517,236 @@ impl Scopes {
         )
     }
 
+    pub fn get_global(&self, name: &str) -> Option<&Value> {
+        self.global.get(name)
+    }
+
+    pub fn get_global_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.global.get_mut(name)
+    }
+
     pub fn get_global_or_insert(&mut self, name: &str, default: Value) -> &mut Value {
         self.global.entry(name.to_owned()).or_insert(default)
     }
 
-    pub fn get_path(&mut self, path: Vec<&str>) -> Option<&mut Value> {
-        self.global.get_mut("filesystem").and_then(|fs| {
-            let mut file = fs;
-            for entry in path {
-                file = match file {
-                    Value::Table(directory) => {
-                        directory.get_mut(&Value::String(entry.to_owned()))?
-                    }
-                    _ => return None,
-                }
+    /// Sets a global variable, returning the previous value if there was one.
+    pub fn set_global(&mut self, name: &str, value: Value) -> Option<Value> {
+        self.global.insert(name.to_owned(), value)
+    }
+
+    pub fn remove_global(&mut self, name: &str) -> Option<Value> {
+        self.global.remove(name)
+    }
+
+    /// Opens a local scope that lives until [`Scopes::pop_scope`], so `let` and `fn`
+    /// declarations from several [`Program::import`] calls can see each other.
+    pub fn push_scope(&mut self) {
+        self.local.push(StackFrame::default());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.local.pop();
+    }
+
+    pub fn globals(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.global
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Seeds the random number generator, so the following random values can be reproduced.
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = Some(StdRng::seed_from_u64(seed));
+    }
+
+    /// Seeded from entropy unless [`Scopes::seed`] was called first.
+    pub fn rng(&mut self) -> &mut StdRng {
+        self.rng.get_or_insert_with(StdRng::from_entropy)
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    /// Advances the clock, calling the timers that come due in the order they are due.
+    /// A timer whose callback fails is cancelled and the error is returned.
+    pub fn advance_clock(
+        &mut self,
+        library: &mut Library,
+        elapsed: std::time::Duration,
+    ) -> Result<()> {
+        let time = self.clock.uptime() + elapsed;
+        for _ in 0..crate::clock::MAX_TICKS {
+            let Some((id, callback)) = self.clock.next_due(time) else {
+                break;
+            };
+            if let Err(error) = callback.call(self, library, Vec::new()) {
+                self.clock.cancel(id);
+                self.clock.set_uptime(time);
+                return Err(error);
             }
-            Some(file)
+        }
+        self.clock.set_uptime(time);
+        Ok(())
+    }
+
+    pub fn vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
+    /// Changes made through this are stamped with the clock's time.
+    pub fn vfs_mut(&mut self) -> &mut Vfs {
+        self.vfs.set_time(self.clock.now());
+        &mut self.vfs
+    }
+
+    /// Contents of a file regardless of permissions, `None` if it doesn't exist or is a directory.
+    pub fn get_path(&self, path: &str) -> Option<&str> {
+        self.vfs.file(path)
+    }
+}
+
+pub type LibFunction<'a> = Rc<dyn Fn(&mut Caller, Vec<Value>) -> Result<Value> + 'a>;
+pub type Intrinsic<'a> = Rc<dyn Fn(&mut Caller, Arguments) -> Result<Value> + 'a>;
+
+/// Host state a file is bound to with [`Vfs::bind`], reading and writing the file calls these.
+#[derive(Clone)]
+pub struct Device<'a> {
+    pub read: Rc<dyn Fn(&mut Caller) -> Result<String> + 'a>,
+    /// Gets the new contents, `None` if the file can't be written.
+    pub write: Option<Rc<dyn Fn(&mut Caller, String) -> Result<()> + 'a>>,
+}
+
+/// Everything a host function can reach while it runs.
+pub struct Caller<'s, 'l, 'a> {
+    pub scopes: &'s mut Scopes,
+    pub library: &'l mut Library<'a>,
+}
+
+/// Unevaluated arguments of an intrinsic call.
+#[derive(Clone, Copy)]
+pub struct Arguments<'e> {
+    expressions: &'e [Expression],
+}
+
+impl Arguments<'_> {
+    pub fn len(&self) -> usize {
+        self.expressions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+
+    /// Evaluates a single argument. It can be evaluated again, e.g. to retry something.
+    pub fn eval(&self, caller: &mut Caller, index: usize) -> Result<Value> {
+        let expression = self
+            .expressions
+            .get(index)
+            .with_context(|| format!("Missing argument #{}!", index + 1))?;
+        Ok(match expression.eval(caller.scopes, caller.library)? {
+            Value::Any(value) => *value,
+            value => value,
         })
     }
+
+    /// Evaluates all arguments in order, the way host functions get them.
+    pub fn eval_all(&self, caller: &mut Caller) -> Result<Vec<Value>> {
+        (0..self.len())
+            .map(|index| self.eval(caller, index))
+            .collect()
+    }
 }
 
-type LibFunction<'a> = Box<dyn FnMut(&mut Scopes, Vec<Value>) -> Result<Value> + 'a>;
+impl Caller<'_, '_, '_> {
+    /// Calls a gclang function, e.g. a callback passed to the host function.
+    pub fn call(&mut self, function: &Function, args: Vec<Value>) -> Result<Value> {
+        function.call(self.scopes, self.library, args)
+    }
 
+    /// Performs an effect right away and returns the value its handler resumed with.
+    pub fn perform(&mut self, effect: Effect) -> Result<Value> {
+        on_effect(effect, self.scopes, self.library)
+    }
+
+    /// Reads a device a file at `path` is bound to.
+    pub fn read_device(&mut self, device: &str, path: &str) -> Result<String> {
+        let Some(device) = self.library.devices.get(device).cloned() else {
+            bail!(late effect "Device isn't connected! Path: '{}'", path);
+        };
+        (device.read)(self)
+    }
+
+    pub fn write_device(&mut self, device: &str, path: &str, content: String) -> Result<()> {
+        let Some(device) = self.library.devices.get(device).cloned() else {
+            bail!(late effect "Device isn't connected! Path: '{}'", path);
+        };
+        let Some(write) = device.write else {
+            bail!(late effect "Read-only file! Path: '{}'", path);
+        };
+        write(self, content)
+    }
+}
+
+/// Host functions available to gclang programs, looked up by name after script functions.
 #[derive(Default)]
 pub struct Library<'a> {
     pub functions: HashMap<String, LibFunction<'a>>,
+    /// Host functions that decide themselves when and how often their arguments
+    /// are evaluated, used for control flow like `for` and `eval`.
+    pub intrinsics: HashMap<String, Intrinsic<'a>>,
+    /// Signatures of functions added with [`Library::register`].
+    pub signatures: HashMap<String, String>,
+    /// Host state files can be bound to, see [`Vfs::bind`].
+    pub devices: HashMap<String, Device<'a>>,
+}
+
+impl<'a> Library<'a> {
+    /// Registers a host function, replacing any function with the same name.
+    pub fn add_function(
+        &mut self,
+        name: &str,
+        function: impl Fn(&mut Caller, Vec<Value>) -> Result<Value> + 'a,
+    ) {
+        self.functions.insert(name.to_owned(), Rc::new(function));
+        self.signatures.remove(name);
+    }
+
+    /// Registers an intrinsic. Intrinsics take precedence over host functions with the same name.
+    pub fn add_intrinsic(
+        &mut self,
+        name: &str,
+        intrinsic: impl Fn(&mut Caller, Arguments) -> Result<Value> + 'a,
+    ) {
+        self.intrinsics.insert(name.to_owned(), Rc::new(intrinsic));
+    }
+
+    /// Registers a device whose files can only be read.
+    pub fn add_device(&mut self, name: &str, read: impl Fn(&mut Caller) -> Result<String> + 'a) {
+        let device = Device {
+            read: Rc::new(read),
+            write: None,
+        };
+        self.devices.insert(name.to_owned(), device);
+    }
+
+    /// Registers a device whose files can be written as well.
+    pub fn add_writable_device(
+        &mut self,
+        name: &str,
+        read: impl Fn(&mut Caller) -> Result<String> + 'a,
+        write: impl Fn(&mut Caller, String) -> Result<()> + 'a,
+    ) {
+        let device = Device {
+            read: Rc::new(read),
+            write: Some(Rc::new(write)),
+        };
+        self.devices.insert(name.to_owned(), device);
+    }
 }
 
 trait Eval {
@@ -986,6 +1305,25 @@ impl Eval for Assign {
 
 impl Eval for FunctionCall {
     fn eval(&self, scopes: &mut Scopes, library: &mut Library) -> Result<Value> {
+        let name = self.name.ident();
+        if scopes.get_function(name).is_none() && scopes.get_included_effect(name).is_none() {
+            if let Some(intrinsic) = library.intrinsics.get(name).cloned() {
+                let mut caller = Caller {
+                    scopes: &mut *scopes,
+                    library: &mut *library,
+                };
+                let args = Arguments {
+                    expressions: &self.args.0,
+                };
+                let result = intrinsic(&mut caller, args);
+                return if let Err(Exception::Effect(effect)) = result {
+                    on_effect(effect, scopes, library)
+                } else {
+                    result
+                };
+            }
+        }
+
         let args = self.args.0.iter();
         let args = args
             .map(|e| {
@@ -996,98 +1334,25 @@ impl Eval for FunctionCall {
             })
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        if let Some(function) = scopes.get_function(self.name.ident()).cloned() {
-            function.eval(scopes, library, args, Some(self.name.ident()))
-        } else if let Some(mut effect) = scopes.get_included_effect(self.name.ident()).cloned() {
+        if let Some(function) = scopes.get_function(name).cloned() {
+            function.eval(scopes, library, args, Some(name))
+        } else if let Some(mut effect) = scopes.get_included_effect(name).cloned() {
             effect.args = args;
             on_effect(effect, scopes, library)
-        } else {
-            let perform = || {
-                if self.name.ident() == "eval" {
-                    match &args[..] {
-                        [Value::String(code)] => {
-                            Program::parse(code)?.eval(scopes, library)?;
-                            Ok(Value::Unit)
-                        }
-                        _ => bail!(r#"Usage: eval("some_global_variable = \"Evaluated\";");"#),
-                    }
-                } else if self.name.ident() == "import" {
-                    match &args[..] {
-                        [Value::String(code)] => {
-                            Program::parse(code)?.import(scopes, library)?;
-                            Ok(Value::Unit)
-                        }
-                        _ => bail!(r#"Usage: import("fn something() {{}}");"#),
-                    }
-                } else if self.name.ident() == "for" {
-                    match &args[..] {
-                        [Value::String(string), Value::Function(body)] => {
-                            for character in string.chars() {
-                                body.eval(
-                                    scopes,
-                                    library,
-                                    vec![Value::String(String::from(character))],
-                                    Some("for loop body"),
-                                )?;
-                            }
-                            Ok(Value::Unit)
-                        }
-                        [Value::Array(array), Value::Function(body)] => {
-                            for element in array {
-                                body.eval(
-                                    scopes,
-                                    library,
-                                    vec![element.clone()],
-                                    Some("for loop body"),
-                                )?;
-                            }
-                            Ok(Value::Unit)
-                        }
-                        [Value::Table(table), Value::Function(body)] => {
-                            for (key, value) in table {
-                                body.eval(
-                                    scopes,
-                                    library,
-                                    vec![key.clone(), value.clone()],
-                                    Some("for loop body"),
-                                )?;
-                            }
-                            Ok(Value::Unit)
-                        }
-                        [Value::Int(initial), Value::Int(limit), Value::Function(body)] => {
-                            for index in *initial..*limit {
-                                body.eval(
-                                    scopes,
-                                    library,
-                                    vec![Value::Int(index)],
-                                    Some("for loop body"),
-                                )?;
-                            }
-                            Ok(Value::Unit)
-                        }
-                        _ => bail!(concat!(
-                            r#"Usage: for([1, 2, 3], fn (number: int) {{ println(number); }});\n"#,
-                            r#"or for(0, 2, fn (index: int) {{ println(index); }});\n"#,
-                            r#"or for({ a = 1; b = 2; }, fn (key: String, value: int) {{ println(key, value); }});"#,
-                        )),
-                    }
-                } else if let Some(function) = library.functions.get_mut(self.name.ident()) {
-                    let result = function(scopes, args);
-                    if let Err(Exception::Effect(effect)) = result {
-                        on_effect(effect, scopes, library)
-                    } else {
-                        result
-                    }
-                } else {
-                    bail!("Function `{}` not found!", self.name.ident());
-                }
+        } else if let Some(function) = library.functions.get(name).cloned() {
+            // * The function is cloned out, so callbacks can call it again
+            let mut caller = Caller {
+                scopes: &mut *scopes,
+                library: &mut *library,
             };
-            let result = perform();
+            let result = function(&mut caller, args);
             if let Err(Exception::Effect(effect)) = result {
                 on_effect(effect, scopes, library)
             } else {
                 result
             }
+        } else {
+            bail!("Function `{}` not found!", name);
         }
     }
 }
@@ -0,0 +1,141 @@
+//! `format("{} is {:>5}", a, b)`, a subset of Rust's formatting syntax.
+//!
+//! A placeholder is `{[argument][:[[fill]align][0][width][type]]}`. The argument is a
+//! position or a name looked up in a table passed as the last argument, align is one of
+//! `<`, `^` and `>`, and the type is `x`, `X`, `o`, `b` or `d` for ints. `{{` and `}}`
+//! are escaped braces.
+use super::executor::{bail, Result, Value};
+
+/// The widest a placeholder can be padded to.
+const MAX_WIDTH: usize = 1024;
+
+pub fn format(template: &str, args: &[Value]) -> Result<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut next_argument = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(char) => placeholder.push(char),
+                        None => bail!("Unclosed '{{' in format string!"),
+                    }
+                }
+                let (argument, spec) = placeholder.split_once(':').unwrap_or((&placeholder, ""));
+                let value = match argument.trim() {
+                    "" => {
+                        next_argument += 1;
+                        args.get(next_argument - 1)
+                    }
+                    argument => match argument.parse::<usize>() {
+                        Ok(index) => args.get(index),
+                        Err(_) => match args.last() {
+                            Some(Value::Table(table)) => {
+                                table.get(&Value::String(argument.to_owned()))
+                            }
+                            _ => None,
+                        },
+                    },
+                };
+                let Some(value) = value else {
+                    bail!("Missing format argument for '{{{}}}'!", placeholder);
+                };
+                output.push_str(&format_value(value, spec)?);
+            }
+            '}' => bail!("Unmatched '}}' in format string!"),
+            char => output.push(char),
+        }
+    }
+    Ok(output)
+}
+
+fn format_value(value: &Value, spec: &str) -> Result<String> {
+    let spec = spec.chars().collect::<Vec<_>>();
+    let is_align = |char: &char| matches!(char, '<' | '^' | '>');
+    let (fill, align, spec) = match &spec[..] {
+        [fill, align, spec @ ..] if is_align(align) => (*fill, Some(*align), spec),
+        [align, spec @ ..] if is_align(align) => (' ', Some(*align), spec),
+        spec => (' ', None, spec),
+    };
+    let (kind, spec) = match spec {
+        [spec @ .., kind] if kind.is_ascii_alphabetic() => (Some(*kind), spec),
+        spec => (None, spec),
+    };
+    let zero_padded = align.is_none() && spec.first() == Some(&'0');
+    let width = match spec.iter().collect::<String>() {
+        width if width.is_empty() => 0,
+        width => match width.parse::<usize>() {
+            Ok(width) if width <= MAX_WIDTH => width,
+            Ok(width) => bail!(
+                "Format width is too large! Width: {}, limit: {}",
+                width,
+                MAX_WIDTH
+            ),
+            Err(_) => bail!("Invalid format width '{}'!", width),
+        },
+    };
+
+    let text = match (kind, value) {
+        (None, value) | (Some('d'), value @ Value::Int(_)) => value.to_string(),
+        (Some('x'), Value::Int(value)) => format!("{:x}", value),
+        (Some('X'), Value::Int(value)) => format!("{:X}", value),
+        (Some('o'), Value::Int(value)) => format!("{:o}", value),
+        (Some('b'), Value::Int(value)) => format!("{:b}", value),
+        (Some(kind), value) => bail!(
+            "Can't format {} with '{}', use one of 'd', 'x', 'X', 'o' and 'b' for ints!",
+            value.type_name(),
+            kind
+        ),
+    };
+
+    let padding = width.saturating_sub(text.chars().count());
+    Ok(match align {
+        _ if zero_padded => match text.strip_prefix('-') {
+            Some(digits) => format!("-{}{}", "0".repeat(padding), digits),
+            None => format!("{}{}", "0".repeat(padding), text),
+        },
+        Some('<') => format!("{}{}", text, fill.to_string().repeat(padding)),
+        Some('^') => format!(
+            "{}{}{}",
+            fill.to_string().repeat(padding / 2),
+            text,
+            fill.to_string().repeat(padding - padding / 2)
+        ),
+        // * Numbers are aligned to the right by default, like in Rust
+        None if matches!(value, Value::Int(_)) => {
+            format!("{}{}", fill.to_string().repeat(padding), text)
+        }
+        None => format!("{}{}", text, fill.to_string().repeat(padding)),
+        Some(_) => format!("{}{}", fill.to_string().repeat(padding), text),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_is_capped() {
+        let args = [Value::Int(7)];
+        assert_eq!(format("{:>1024}", &args).unwrap().len(), MAX_WIDTH);
+        let error = format("{:>1025}", &args)
+            .unwrap_err()
+            .message()
+            .unwrap_or_default();
+        assert_eq!(error, "Format width is too large! Width: 1025, limit: 1024");
+        let error = format("{:99999999999999999999999}", &args).unwrap_err();
+        let error = error.message().unwrap_or_default();
+        assert_eq!(error, "Invalid format width '99999999999999999999999'!");
+    }
+}
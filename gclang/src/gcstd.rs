@@ -0,0 +1,1611 @@
+use super::*;
+use map_macro::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const FILTER_USAGE: &str = "filter([1, 2, 3], fn (number: int) { return number > 1; })";
+const ANY_USAGE: &str = "any([1, 2, 3], fn (number: int) { return number > 1; })";
+const ALL_USAGE: &str = "all([1, 2, 3], fn (number: int) { return number > 1; })";
+const FILTER_KEYS_USAGE: &str =
+    r#"filter_keys({ a = 1; b = 2; }, fn (key: String) { return key != "a"; })"#;
+/// The longest string, in bytes, that `repeat` and the padding functions build.
+const MAX_STRING_LEN: usize = 1 << 20;
+/// The longest array `range` builds.
+const MAX_RANGE_LEN: usize = 1 << 20;
+const SORT_USAGE: &str = "sort([3, 1, 2], fn (a: int, b: int) { return b - a; })";
+
+impl Library<'_> {
+    pub fn with_std() -> Self {
+        let mut library = Self::default();
+        // * ------------------------------------- Math ------------------------------------- * //
+        library.register("min", |_: &mut Caller, values: Variadic<i32>| {
+            Ok(values.0.into_iter().min().context("Usage: min(1, 2, 3)")?)
+        });
+        library.register("max", |_: &mut Caller, values: Variadic<i32>| {
+            Ok(values.0.into_iter().max().context("Usage: max(1, 2, 3)")?)
+        });
+        library.register("abs", |_: &mut Caller, value: i32| Ok(value.wrapping_abs()));
+        library.register("sign", |_: &mut Caller, value: i32| Ok(value.signum()));
+        library.register("clamp", |_: &mut Caller, value: i32, min: i32, max: i32| {
+            ensure!(min <= max, "Usage: clamp(value, 0, 10)");
+            Ok(value.clamp(min, max))
+        });
+        library.register("pow", |_: &mut Caller, base: i32, exponent: i32| {
+            ensure!(exponent >= 0, "Usage: pow(2, 10)");
+            match base.checked_pow(exponent as _) {
+                Some(value) => Ok(value),
+                None => bail!(late effect "Integer overflow! pow({}, {})", base, exponent),
+            }
+        });
+        library.register("sqrt", |_: &mut Caller, value: i32| {
+            if value < 0 {
+                bail!(late effect "Square root of a negative number! Value: {}", value);
+            }
+            // * Integer square root, rounded down and corrected for float precision
+            let value = value as i64;
+            let mut root = (value as f64).sqrt() as i64;
+            while root * root > value {
+                root -= 1;
+            }
+            while (root + 1) * (root + 1) <= value {
+                root += 1;
+            }
+            Ok(root as i32)
+        });
+        library.register(
+            "bit_and",
+            |_: &mut Caller, lhs: i32, rhs: i32| Ok(lhs & rhs),
+        );
+        library.register("bit_or", |_: &mut Caller, lhs: i32, rhs: i32| Ok(lhs | rhs));
+        library.register(
+            "bit_xor",
+            |_: &mut Caller, lhs: i32, rhs: i32| Ok(lhs ^ rhs),
+        );
+        library.register("bit_not", |_: &mut Caller, value: i32| Ok(!value));
+        library.register("shl", |_: &mut Caller, value: i32, shift: i32| {
+            ensure!((0..32).contains(&shift), "Usage: shl(1, 4)");
+            Ok(value << shift)
+        });
+        library.register("shr", |_: &mut Caller, value: i32, shift: i32| {
+            ensure!((0..32).contains(&shift), "Usage: shr(16, 4)");
+            Ok(value >> shift)
+        });
+        // * ------------------------------------ Random ------------------------------------ * //
+        library.register("seed", |caller: &mut Caller, seed: i32| {
+            caller.scopes.seed(seed as u32 as u64);
+            Ok(())
+        });
+        library.register(
+            "random",
+            |caller: &mut Caller, start: i32, end: Option<i32>| {
+                let (start, end) = end.map_or((0, start), |end| (start, end));
+                ensure!(start < end, "Usage: random(6) or random(1, 7)");
+                Ok(caller.scopes.rng().gen_range(start..end))
+            },
+        );
+        library.register("random_bool", |caller: &mut Caller| {
+            Ok(caller.scopes.rng().gen::<bool>())
+        });
+        library.register("shuffle", |caller: &mut Caller, mut array: Vec<Value>| {
+            array.shuffle(caller.scopes.rng());
+            Ok(array)
+        });
+        library.register(
+            "choose",
+            |caller: &mut Caller, array: Vec<Value>| match array.choose(caller.scopes.rng()) {
+                Some(value) => Ok(value.clone()),
+                None => bail!(late effect "Choosing from an empty array!"),
+            },
+        );
+        // * ---------------------------------- Containers ---------------------------------- * //
+        library.register("len", |_: &mut Caller, value: Value| {
+            Ok(match value {
+                Value::String(value) => value.len(),
+                Value::Array(value) => value.len(),
+                Value::Table(value) => value.len(),
+                _ => bail!(r#"Usage: len("Some text") or len({{0 = "Some table";}})"#),
+            })
+        });
+        library.register("pop", |_: &mut Caller, value: Value| {
+            Ok(match value {
+                Value::String(mut value) => {
+                    value.pop();
+                    Value::String(value)
+                }
+                Value::Array(mut value) => {
+                    value.pop();
+                    Value::Array(value)
+                }
+                _ => bail!(r#"Usage: string = pop(string);"#),
+            })
+        });
+        library.register("remove", |_: &mut Caller, value: Value, index: Value| {
+            let out_of_bounds = |len: usize, index: i32| index < 0 || index as usize >= len;
+            Ok(match (value, index) {
+                (Value::String(mut value), Value::Int(index)) => {
+                    if out_of_bounds(value.len(), index) {
+                        bail!(late effect "Remove index is out of bounds! Index: {}", index);
+                    }
+                    if !value.is_char_boundary(index as _) {
+                        bail!(late effect "Remove index splits a character! Index: {}", index);
+                    }
+                    value.remove(index as _);
+                    Value::String(value)
+                }
+                (Value::Array(mut value), Value::Int(index)) => {
+                    if out_of_bounds(value.len(), index) {
+                        bail!(late effect "Remove index is out of bounds! Index: {}", index);
+                    }
+                    value.remove(index as _);
+                    Value::Array(value)
+                }
+                (Value::Table(mut value), index) => {
+                    if value.remove(&index).is_none() {
+                        bail!(late effect "Removing non-existing key from table!");
+                    }
+                    Value::Table(value)
+                }
+                _ => bail!(
+                    r#"Usage: string = remove("Some text", 8); or table = remove({{ a = 1; }}, "a");"#
+                ),
+            })
+        });
+        library.register("contains", |_: &mut Caller, value: Value, key: Value| {
+            Ok(match (value, key) {
+                (Value::String(value), Value::String(key)) => value.contains(&key),
+                (Value::Array(value), key) => value.contains(&key),
+                (Value::Table(value), key) => value.contains_key(&key),
+                _ => bail!(r#"Usage: contains("Some text", "te")"#),
+            })
+        });
+        // * ------------------------------------ Arrays ------------------------------------ * //
+        library.register(
+            "push",
+            |_: &mut Caller, mut array: Vec<Value>, value: Value| {
+                array.push(value);
+                Ok(array)
+            },
+        );
+        library.register(
+            "insert",
+            |_: &mut Caller, mut array: Vec<Value>, index: i32, value: Value| {
+                if index < 0 || index as usize > array.len() {
+                    bail!(late effect "Insert index is out of bounds! Index: {}", index);
+                }
+                array.insert(index as _, value);
+                Ok(array)
+            },
+        );
+        library.register(
+            "slice",
+            |_: &mut Caller, array: Vec<Value>, start: i32, end: Option<i32>| {
+                let range = slice_range(array.len(), start, end)?;
+                Ok(array[range].to_vec())
+            },
+        );
+        library.register("reverse", |_: &mut Caller, mut array: Vec<Value>| {
+            array.reverse();
+            Ok(array)
+        });
+        library.register(
+            "sort",
+            |caller: &mut Caller, mut array: Vec<Value>, compare: Option<Function>| {
+                let Some(compare) = compare else {
+                    array.sort();
+                    return Ok(array);
+                };
+                merge_sort(caller, array, &compare)
+            },
+        );
+        library.register(
+            "map",
+            |caller: &mut Caller, array: Vec<Value>, function: Function| {
+                array
+                    .into_iter()
+                    .map(|value| caller.call(&function, vec![value]))
+                    .collect::<Result<Vec<_>>>()
+            },
+        );
+        library.register(
+            "filter",
+            |caller: &mut Caller, array: Vec<Value>, predicate: Function| {
+                let mut filtered = Vec::with_capacity(array.len());
+                for value in array {
+                    if test(caller, &predicate, vec![value.clone()], FILTER_USAGE)? {
+                        filtered.push(value);
+                    }
+                }
+                Ok(filtered)
+            },
+        );
+        library.register(
+            "reduce",
+            |caller: &mut Caller, array: Vec<Value>, function: Function, initial: Option<Value>| {
+                let mut array = array.into_iter();
+                let Some(mut accumulator) = initial.or_else(|| array.next()) else {
+                    bail!(late effect "Reducing an empty array without an initial value!");
+                };
+                for value in array {
+                    accumulator = caller.call(&function, vec![accumulator, value])?;
+                }
+                Ok(accumulator)
+            },
+        );
+        library.register(
+            "any",
+            |caller: &mut Caller, array: Vec<Value>, predicate: Function| {
+                for value in array {
+                    if test(caller, &predicate, vec![value], ANY_USAGE)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            },
+        );
+        library.register(
+            "all",
+            |caller: &mut Caller, array: Vec<Value>, predicate: Function| {
+                for value in array {
+                    if !test(caller, &predicate, vec![value], ALL_USAGE)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            },
+        );
+        library.register(
+            "index_of",
+            |_: &mut Caller, array: Vec<Value>, value: Value| {
+                Ok(array
+                    .iter()
+                    .position(|element| *element == value)
+                    .map_or(-1, |index| index as i32))
+            },
+        );
+        library.register(
+            "range",
+            |_: &mut Caller, start: i32, end: Option<i32>, step: Option<i32>| {
+                let (start, end) = end.map_or((0, start), |end| (start, end));
+                let step = step.unwrap_or(1);
+                ensure!(step != 0, "Usage: range(10) or range(0, 10, 2)");
+                let mut range = Vec::new();
+                let mut value = start;
+                while (step > 0 && value < end) || (step < 0 && value > end) {
+                    if range.len() == MAX_RANGE_LEN {
+                        bail!(late effect "Range is too long! Limit: {} values", MAX_RANGE_LEN);
+                    }
+                    range.push(value);
+                    value = match value.checked_add(step) {
+                        Some(value) => value,
+                        None => break,
+                    };
+                }
+                Ok(range)
+            },
+        );
+        library.register("zip", |_: &mut Caller, lhs: Vec<Value>, rhs: Vec<Value>| {
+            Ok(lhs
+                .into_iter()
+                .zip(rhs)
+                .map(|(lhs, rhs)| vec![lhs, rhs])
+                .collect::<Vec<_>>())
+        });
+        // * ------------------------------------ Tables ------------------------------------ * //
+        library.register("keys", |_: &mut Caller, table: BTreeMap<Value, Value>| {
+            Ok(table.into_keys().collect::<Vec<_>>())
+        });
+        library.register("values", |_: &mut Caller, table: BTreeMap<Value, Value>| {
+            Ok(table.into_values().collect::<Vec<_>>())
+        });
+        library.register(
+            "entries",
+            |_: &mut Caller, table: BTreeMap<Value, Value>| {
+                Ok(table
+                    .into_iter()
+                    .map(|(key, value)| vec![key, value])
+                    .collect::<Vec<_>>())
+            },
+        );
+        library.register(
+            "get",
+            |_: &mut Caller,
+             mut table: BTreeMap<Value, Value>,
+             key: Value,
+             default: Option<Value>| {
+                match table.remove(&key).or(default) {
+                    Some(value) => Ok(value),
+                    None => bail!(late effect "Key not found in table! Key: {}", key.to_string()),
+                }
+            },
+        );
+        library.register(
+            "merge",
+            |caller: &mut Caller,
+             lhs: BTreeMap<Value, Value>,
+             rhs: BTreeMap<Value, Value>,
+             policy: Option<Value>| {
+                let policy = policy.unwrap_or_else(|| Value::String(String::from("replace")));
+                merge(caller, lhs, rhs, &policy)
+            },
+        );
+        library.register(
+            "filter_keys",
+            |caller: &mut Caller, table: BTreeMap<Value, Value>, predicate: Function| {
+                let mut filtered = BTreeMap::new();
+                for (key, value) in table {
+                    if test(caller, &predicate, vec![key.clone()], FILTER_KEYS_USAGE)? {
+                        filtered.insert(key, value);
+                    }
+                }
+                Ok(filtered)
+            },
+        );
+        // * Values are never shared, so a clone is always deep
+        library.register("clone", |_: &mut Caller, value: Value| Ok(value));
+        library.register("equals", |_: &mut Caller, lhs: Value, rhs: Value| {
+            Ok(lhs == rhs)
+        });
+        // * ------------------------------------- Time ------------------------------------- * //
+        library.register("now", |caller: &mut Caller| {
+            Ok(caller.scopes.clock().now().as_secs().min(i32::MAX as u64) as i32)
+        });
+        library.register("date", |caller: &mut Caller, time: Option<i32>| {
+            Ok(match time {
+                Some(time) => clock::format_date(Duration::from_secs(time.max(0) as _)),
+                None => clock::format_date(caller.scopes.clock().now()),
+            })
+        });
+        library.register("uptime", |caller: &mut Caller| {
+            Ok(caller
+                .scopes
+                .clock()
+                .uptime()
+                .as_millis()
+                .min(i32::MAX as u128) as i32)
+        });
+        library.register(
+            "after",
+            |caller: &mut Caller, delay: i32, callback: Function| {
+                ensure!(
+                    delay >= 0,
+                    r#"Usage: after(1000, fn () {{ println("Later"); }})"#
+                );
+                let delay = Duration::from_millis(delay as u64);
+                Ok(caller.scopes.clock_mut().schedule(delay, None, callback))
+            },
+        );
+        library.register(
+            "every",
+            |caller: &mut Caller, interval: i32, callback: Function| {
+                ensure!(
+                    interval > 0,
+                    r#"Usage: every(1000, fn () {{ println("Tick"); }})"#
+                );
+                let interval = Duration::from_millis(interval as u64);
+                Ok(caller
+                    .scopes
+                    .clock_mut()
+                    .schedule(interval, Some(interval), callback))
+            },
+        );
+        library.register("cancel", |caller: &mut Caller, timer: i32| {
+            Ok(caller.scopes.clock_mut().cancel(timer))
+        });
+        // * --------------------------------- Control flow --------------------------------- * //
+        library.add_intrinsic("eval", |caller: &mut Caller, args: Arguments| {
+            match &args.eval_all(caller)?[..] {
+                [Value::String(code)] => {
+                    Program::parse(code)?.eval(caller.scopes, caller.library)?;
+                    Ok(Value::Unit)
+                }
+                _ => bail!(r#"Usage: eval("some_global_variable = \"Evaluated\";");"#),
+            }
+        });
+        library.add_intrinsic("import", |caller: &mut Caller, args: Arguments| match &args
+            .eval_all(caller)?[..]
+        {
+            [Value::String(code)] => {
+                Program::parse(code)?.import(caller.scopes, caller.library)?;
+                Ok(Value::Unit)
+            }
+            _ => bail!(r#"Usage: import("fn something() {{}}");"#),
+        });
+        library.add_intrinsic("for", |caller: &mut Caller, args: Arguments| {
+            match &args.eval_all(caller)?[..] {
+                [Value::String(string), Value::Function(body)] => {
+                    for character in string.chars() {
+                        caller.call(body, vec![Value::String(String::from(character))])?;
+                    }
+                    Ok(Value::Unit)
+                }
+                [Value::Array(array), Value::Function(body)] => {
+                    for element in array {
+                        caller.call(body, vec![element.clone()])?;
+                    }
+                    Ok(Value::Unit)
+                }
+                [Value::Table(table), Value::Function(body)] => {
+                    for (key, value) in table {
+                        caller.call(body, vec![key.clone(), value.clone()])?;
+                    }
+                    Ok(Value::Unit)
+                }
+                [Value::Int(initial), Value::Int(limit), Value::Function(body)] => {
+                    for index in *initial..*limit {
+                        caller.call(body, vec![Value::Int(index)])?;
+                    }
+                    Ok(Value::Unit)
+                }
+                _ => bail!(concat!(
+                    r#"Usage: for([1, 2, 3], fn (number: int) {{ println(number); }});\n"#,
+                    r#"or for(0, 2, fn (index: int) {{ println(index); }});\n"#,
+                    r#"or for({ a = 1; b = 2; }, fn (key: String, value: int) {{ println(key, value); }});"#,
+                )),
+            }
+        });
+        // * ------------------------------------ Strings ----------------------------------- * //
+        library.register("trim", |_: &mut Caller, value: String| {
+            Ok(value.trim().to_owned())
+        });
+        library.register(
+            "format",
+            |_: &mut Caller, template: String, args: Variadic<Value>| {
+                format::format(&template, &args.0)
+            },
+        );
+        library.register(
+            "split",
+            |_: &mut Caller, value: String, separator: Option<String>| {
+                Ok(match separator {
+                    Some(separator) if separator.is_empty() => {
+                        bail!(r#"Usage: split("a b c", " ") or split("a  b c")"#)
+                    }
+                    Some(separator) => value.split(&separator).map(String::from).collect(),
+                    None => value
+                        .split_whitespace()
+                        .map(String::from)
+                        .collect::<Vec<_>>(),
+                })
+            },
+        );
+        library.register(
+            "join",
+            |_: &mut Caller, values: Vec<Value>, separator: String| {
+                Ok(values
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(&separator))
+            },
+        );
+        library.register("lines", |_: &mut Caller, value: String| {
+            Ok(value.lines().map(String::from).collect::<Vec<_>>())
+        });
+        library.register(
+            "substring",
+            |_: &mut Caller, value: String, start: i32, end: Option<i32>| {
+                let range = slice_range(value.len(), start, end)?;
+                Ok(value
+                    .get(range)
+                    .with_context(|| format!("Substring splits a character! Start: {}", start))?
+                    .to_owned())
+            },
+        );
+        library.register("find", |_: &mut Caller, value: String, pattern: String| {
+            Ok(value.find(&pattern).map_or(-1, |index| index as i32))
+        });
+        library.register("rfind", |_: &mut Caller, value: String, pattern: String| {
+            Ok(value.rfind(&pattern).map_or(-1, |index| index as i32))
+        });
+        library.register(
+            "replace",
+            |_: &mut Caller, value: String, from: String, to: String, count: Option<i32>| {
+                ensure!(!from.is_empty(), r#"Usage: replace("a-b-c", "-", "+", 1)"#);
+                Ok(match count {
+                    Some(count) => value.replacen(&from, &to, count.max(0) as _),
+                    None => value.replace(&from, &to),
+                })
+            },
+        );
+        library.register(
+            "starts_with",
+            |_: &mut Caller, value: String, prefix: String| Ok(value.starts_with(&prefix)),
+        );
+        library.register(
+            "ends_with",
+            |_: &mut Caller, value: String, suffix: String| Ok(value.ends_with(&suffix)),
+        );
+        library.register("upper", |_: &mut Caller, value: String| {
+            Ok(value.to_uppercase())
+        });
+        library.register("lower", |_: &mut Caller, value: String| {
+            Ok(value.to_lowercase())
+        });
+        library.register("repeat", |_: &mut Caller, value: String, count: i32| {
+            ensure!(count >= 0, r#"Usage: repeat("-", 10)"#);
+            ensure_string_len(value.len().checked_mul(count as _))?;
+            Ok(value.repeat(count as _))
+        });
+        library.register(
+            "pad_left",
+            |_: &mut Caller, value: String, width: i32, fill: Option<String>| {
+                let padding = padding(&value, width, fill, r#"Usage: pad_left("7", 3, "0")"#)?;
+                Ok(padding + &value)
+            },
+        );
+        library.register(
+            "pad_right",
+            |_: &mut Caller, value: String, width: i32, fill: Option<String>| {
+                let padding = padding(&value, width, fill, r#"Usage: pad_right("Name", 10)"#)?;
+                Ok(value + &padding)
+            },
+        );
+        library.register(
+            "char_code",
+            |_: &mut Caller, value: String, index: Option<i32>| {
+                let index = index.unwrap_or(0);
+                match value
+                    .get(index.max(0) as usize..)
+                    .and_then(|rest| rest.chars().next())
+                {
+                    Some(char) if index >= 0 => Ok(char as i32),
+                    _ => bail!(late effect "Char code index is out of bounds! Index: {}", index),
+                }
+            },
+        );
+        library.register("from_char_code", |_: &mut Caller, codes: Variadic<i32>| {
+            codes
+                .0
+                .into_iter()
+                .map(|code| {
+                    char::from_u32(code as _)
+                        .with_context(|| format!("Invalid char code! Code: {}", code))
+                })
+                .collect::<std::result::Result<String, _>>()
+                .map_err(Exception::Error)
+        });
+        library.register(
+            "parse_int",
+            |_: &mut Caller, value: String, radix: Option<i32>| {
+                let radix = radix.unwrap_or(10);
+                ensure!((2..=36).contains(&radix), r#"Usage: parse_int("ff", 16)"#);
+                match i32::from_str_radix(value.trim(), radix as _) {
+                    std::result::Result::Ok(number) => Ok(number),
+                    Err(_) => bail!(late effect "Failed to parse '{}' as int!", value),
+                }
+            },
+        );
+        // * ----------------------------------- Patterns ----------------------------------- * //
+        library.register(
+            "glob_match",
+            |_: &mut Caller, pattern: String, text: String| {
+                Ok(pattern::glob_match(&pattern, &text))
+            },
+        );
+        library.register(
+            "regex_match",
+            |_: &mut Caller, pattern: String, text: String| {
+                let text = text.chars().collect::<Vec<_>>();
+                Ok(regex(&pattern)?.is_match(&text))
+            },
+        );
+        library.register(
+            "regex_find_all",
+            |_: &mut Caller, pattern: String, text: String| {
+                let text = text.chars().collect::<Vec<_>>();
+                Ok(regex(&pattern)?
+                    .find_all(&text)
+                    .into_iter()
+                    .map(|captures| group(&text, &captures, 0))
+                    .collect::<Vec<_>>())
+            },
+        );
+        library.register(
+            "regex_replace",
+            |_: &mut Caller, pattern: String, text: String, replacement: String| {
+                let text = text.chars().collect::<Vec<_>>();
+                let mut replaced = String::with_capacity(text.len());
+                let mut last = 0;
+                for captures in regex(&pattern)?.find_all(&text) {
+                    let range = captures[0].clone().unwrap_or_default();
+                    replaced.extend(&text[last..range.start]);
+                    // * `$0` is the whole match, `$1` to `$9` are groups and `$$` is a `$`
+                    let mut chars = replacement.chars().peekable();
+                    while let Some(char) = chars.next() {
+                        match (char, chars.peek().copied()) {
+                            ('$', Some('$')) => {
+                                chars.next();
+                                replaced.push('$');
+                            }
+                            ('$', Some(digit @ '0'..='9')) => {
+                                chars.next();
+                                let index = digit as usize - '0' as usize;
+                                replaced.push_str(&group(&text, &captures, index));
+                            }
+                            (char, _) => replaced.push(char),
+                        }
+                    }
+                    last = range.end;
+                }
+                replaced.extend(&text[last..]);
+                Ok(replaced)
+            },
+        );
+        // * ------------------------------------- Types ------------------------------------ * //
+        library.register("type_of", |_: &mut Caller, value: Value| {
+            Ok(value.type_name().to_owned())
+        });
+        macro_rules! is_type {
+            ($($name: literal => $type: literal,)*) => {
+                $(library.register($name, |_: &mut Caller, value: Value| {
+                    Ok(value.type_name() == $type)
+                });)*
+            };
+        }
+        is_type! {
+            "is_int" => "int",
+            "is_bool" => "bool",
+            "is_string" => "String",
+            "is_array" => "Array",
+            "is_table" => "Table",
+            "is_function" => "fn",
+            "is_unit" => "Unit",
+        }
+        library.register("to_int", |_: &mut Caller, value: Value| {
+            Ok(match value {
+                Value::Int(value) => value,
+                Value::Bool(value) => value as i32,
+                Value::String(string) => match string.trim().parse() {
+                    std::result::Result::Ok(value) => value,
+                    Err(_) => bail!(late effect "Failed to convert '{}' to int!", string),
+                },
+                value => bail!(late effect "Can't convert {} to int!", value.type_name()),
+            })
+        });
+        library.register("to_string", |_: &mut Caller, value: Value| {
+            Ok(value.to_string())
+        });
+        library.register("to_bool", |_: &mut Caller, value: Value| {
+            Ok(match value {
+                Value::Bool(value) => value,
+                Value::Int(value) => value != 0,
+                Value::String(string) => match string.trim() {
+                    "true" => true,
+                    "false" => false,
+                    _ => bail!(late effect "Failed to convert '{}' to bool!", string),
+                },
+                value => bail!(late effect "Can't convert {} to bool!", value.type_name()),
+            })
+        });
+        library.register("repr", |_: &mut Caller, value: Value| Ok(value.repr()));
+        // * ------------------------------------- Data ------------------------------------- * //
+        library.register(
+            "to_json",
+            |_: &mut Caller, value: Value, pretty: Option<bool>| {
+                data::to_json(&value, pretty.unwrap_or(false))
+            },
+        );
+        library.register("from_json", |_: &mut Caller, text: String| {
+            data::from_json(&text)
+        });
+        library.register(
+            "to_ron",
+            |_: &mut Caller, value: Value, pretty: Option<bool>| {
+                data::to_ron(&value, pretty.unwrap_or(false))
+            },
+        );
+        library.register("from_ron", |_: &mut Caller, text: String| {
+            data::from_ron(&text)
+        });
+        // * ----------------------------------- Encoding ----------------------------------- * //
+        library.register("base64_encode", |_: &mut Caller, text: String| {
+            Ok(encoding::base64_encode(text.as_bytes()))
+        });
+        library.register("base64_decode", |_: &mut Caller, text: String| {
+            match encoding::base64_decode(&text).map(String::from_utf8) {
+                Some(std::result::Result::Ok(decoded)) => Ok(decoded),
+                Some(Err(_)) => bail!(late effect "Decoded base64 isn't valid text!"),
+                None => bail!(late effect "Invalid base64! Text: '{}'", text),
+            }
+        });
+        library.register("hex_encode", |_: &mut Caller, text: String| {
+            Ok(encoding::hex_encode(text.as_bytes()))
+        });
+        library.register(
+            "hex_decode",
+            |_: &mut Caller, text: String| match encoding::hex_decode(&text).map(String::from_utf8)
+            {
+                Some(std::result::Result::Ok(decoded)) => Ok(decoded),
+                Some(Err(_)) => bail!(late effect "Decoded hex isn't valid text!"),
+                None => bail!(late effect "Invalid hex! Text: '{}'", text),
+            },
+        );
+        library.register("crc32", |_: &mut Caller, text: String| {
+            Ok(format!("{:08x}", encoding::crc32(text.as_bytes())))
+        });
+        library.register("sha256", |_: &mut Caller, text: String| {
+            Ok(encoding::hex_encode(&encoding::sha256(text.as_bytes())))
+        });
+        // * ---------------------------------- Filesystem ---------------------------------- * //
+        library.register("fs_read", |caller: &mut Caller, path: String| {
+            if let Some(device) = caller.scopes.vfs().device(&path, vfs::Access::Read)? {
+                let device = device.to_owned();
+                return caller.read_device(&device, &path);
+            }
+            Ok(caller.scopes.vfs().read(&path)?.into_owned())
+        });
+        library.register(
+            "fs_write",
+            |caller: &mut Caller, path: String, content: String| {
+                if let Some(device) = caller.scopes.vfs().device(&path, vfs::Access::Write)? {
+                    let device = device.to_owned();
+                    return caller.write_device(&device, &path, content);
+                }
+                caller.scopes.vfs_mut().write(&path, content)
+            },
+        );
+        library.register("fs_list", |caller: &mut Caller, path: String| {
+            caller.scopes.vfs().list(&path)
+        });
+        library.register("fs_mkdir", |caller: &mut Caller, path: String| {
+            caller.scopes.vfs_mut().mkdir(&path)
+        });
+        library.register(
+            "fs_remove",
+            |caller: &mut Caller, path: String, recursive: Option<bool>| {
+                caller
+                    .scopes
+                    .vfs_mut()
+                    .remove(&path, recursive.unwrap_or(false))
+            },
+        );
+        library.register("fs_exists", |caller: &mut Caller, path: String| {
+            Ok(caller.scopes.vfs().exists(&path))
+        });
+        library.register("fs_resolve", |caller: &mut Caller, path: String| {
+            Ok(caller.scopes.vfs().resolve(&path))
+        });
+        library.register("fs_cwd", |caller: &mut Caller| {
+            Ok(caller.scopes.vfs().working_directory().to_owned())
+        });
+        library.register("fs_chdir", |caller: &mut Caller, path: Option<String>| {
+            let vfs = caller.scopes.vfs_mut();
+            let path = path.unwrap_or_else(|| vfs.home_directory().to_owned());
+            vfs.set_working_directory(&path)
+        });
+        library.register("fs_home", |caller: &mut Caller| {
+            Ok(caller.scopes.vfs().home_directory().to_owned())
+        });
+        library.register("fs_stat", |caller: &mut Caller, path: String| {
+            Ok(stat_table(caller.scopes.vfs().stat(&path)?))
+        });
+        library.register("fs_lstat", |caller: &mut Caller, path: String| {
+            Ok(stat_table(caller.scopes.vfs().lstat(&path)?))
+        });
+        library.register(
+            "fs_link",
+            |caller: &mut Caller, existing: String, path: String| {
+                caller.scopes.vfs_mut().link(&existing, &path)
+            },
+        );
+        library.register(
+            "fs_symlink",
+            |caller: &mut Caller, target: String, path: String| {
+                caller.scopes.vfs_mut().symlink(&target, &path)
+            },
+        );
+        library.register("fs_readlink", |caller: &mut Caller, path: String| {
+            Ok(caller.scopes.vfs().readlink(&path)?.to_owned())
+        });
+        library.register(
+            "fs_access",
+            |caller: &mut Caller, path: String, access: String| {
+                let Some(access) = vfs::Access::parse(&access) else {
+                    bail!(r#"Usage: fs_access("/bin/ls", "x"), with one of "r", "w" and "x""#);
+                };
+                Ok(caller.scopes.vfs().access(&path, access))
+            },
+        );
+        library.register(
+            "fs_chmod",
+            |caller: &mut Caller, path: String, mode: i32| {
+                ensure!(
+                    (0..=0o777).contains(&mode),
+                    r#"Usage: fs_chmod("/bin/ls", parse_int("755", 8))"#
+                );
+                caller.scopes.vfs_mut().chmod(&path, mode as _)
+            },
+        );
+        library.register(
+            "fs_chown",
+            |caller: &mut Caller, path: String, owner: String, group: Option<String>| {
+                let owner = Some(owner.as_str()).filter(|owner| !owner.is_empty());
+                caller
+                    .scopes
+                    .vfs_mut()
+                    .chown(&path, owner, group.as_deref())
+            },
+        );
+        library.register("fs_snapshot", |caller: &mut Caller, name: String| {
+            caller.scopes.vfs_mut().snapshot(&name);
+            Ok(())
+        });
+        library.register("fs_rollback", |caller: &mut Caller, name: String| {
+            caller.scopes.vfs_mut().rollback(&name)
+        });
+        library.register("fs_snapshots", |caller: &mut Caller| {
+            Ok(caller
+                .scopes
+                .vfs()
+                .snapshots()
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>())
+        });
+        library.register("fs_forget", |caller: &mut Caller, name: String| {
+            caller.scopes.vfs_mut().forget_snapshot(&name)
+        });
+        library.register("fs_diff", |caller: &mut Caller, path: Option<String>| {
+            let changes = caller
+                .scopes
+                .vfs()
+                .diff(&Vfs::builtin(), path.as_deref().unwrap_or("/"));
+            Ok(Value::Table(
+                changes
+                    .into_iter()
+                    .map(|(path, change)| {
+                        (Value::String(path), Value::String(change.name().to_owned()))
+                    })
+                    .collect(),
+            ))
+        });
+        library.register("fs_restore", |caller: &mut Caller, path: String| {
+            caller.scopes.vfs_mut().restore(&Vfs::builtin(), &path)
+        });
+        library.register("fs_factory_reset", |caller: &mut Caller| {
+            caller.scopes.vfs_mut().factory_reset(&Vfs::builtin())
+        });
+        // * ------------------------------------- Users ------------------------------------ * //
+        library.register("whoami", |caller: &mut Caller| {
+            Ok(caller.scopes.vfs().user().0.to_owned())
+        });
+        library.register("groups", |caller: &mut Caller, name: Option<String>| {
+            let vfs = caller.scopes.vfs();
+            Ok(match name {
+                Some(name) => match vfs.account(&name) {
+                    Some(account) => vfs.groups_of(&account),
+                    None => bail!(late effect "User not found! Name: '{}'", name),
+                },
+                None => vfs.user().1.to_vec(),
+            })
+        });
+        library.register(
+            "su",
+            |caller: &mut Caller, name: String, password: Option<String>| {
+                let vfs = caller.scopes.vfs_mut();
+                if vfs.account(&name).is_none() {
+                    bail!(late effect "User not found! Name: '{}'", name);
+                }
+                let password = password.unwrap_or_default();
+                if vfs.user().0 != "root" && !vfs.verify_password(&name, &password) {
+                    return Ok(false);
+                }
+                vfs.switch_user(&name)?;
+                Ok(true)
+            },
+        );
+        library.register(
+            "sudo",
+            |caller: &mut Caller, password: String, command: Function| {
+                let vfs = caller.scopes.vfs_mut();
+                let (user, groups) = vfs.user();
+                let (user, groups) = (user.to_owned(), groups.to_vec());
+                if user != "root" {
+                    if !groups.iter().any(|group| group == "sudo") {
+                        bail!(late effect "{} is not in the sudoers file!", user);
+                    }
+                    if !vfs.verify_password(&user, &password) {
+                        return Ok(false);
+                    }
+                }
+                let home = vfs.home_directory().to_owned();
+                vfs.switch_user("root")?;
+                let result = command.call(caller.scopes, caller.library, Vec::new());
+                let vfs = caller.scopes.vfs_mut();
+                vfs.set_user(&user, groups);
+                vfs.set_home_directory(&home);
+                result.map(|_| true)
+            },
+        );
+        // * ------------------------------------- Misc ------------------------------------- * //
+        library.register("help", |caller: &mut Caller, name: Option<String>| {
+            Ok(match name {
+                Some(name) => caller
+                    .library
+                    .signature(&name)
+                    .with_context(|| format!("No help for '{}'!", name))?
+                    .to_owned(),
+                None => {
+                    let mut signatures = caller
+                        .library
+                        .signatures
+                        .values()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    signatures.sort();
+                    signatures.join("\n")
+                }
+            })
+        });
+        library
+    }
+}
+
+impl Vfs {
+    /// The filesystem every new game starts with.
+    pub fn builtin() -> Self {
+        macro_rules! define_file {
+            ($($path: literal = $value: expr;)*) => {
+                Value::Table(btree_map! {
+                    $(Value::String(String::from($path)) => $value,)*
+                })
+            };
+            ($path: literal) => {
+                Value::String(String::from(include_str!($path)))
+            };
+        }
+
+        macro_rules! embed_file {
+            ($content: literal) => {
+                Value::String(String::from($content))
+            };
+        }
+
+        let files = define_file! {
+            "etc" = define_file! {
+                "passwd" = embed_file!("root:x:0:0:root:/root:/bin/gcsh\ngc:x:1000:1000:Garbage Collector:/home:/bin/gcsh\n");
+                "group" = embed_file!("root:x:0:\nsudo:x:27:\ngc:x:1000:\n");
+                // * Passwords are SHA-256 hashes, the garbage collector has none
+                "shadow" = embed_file!("root:0736fd5b7cc7ab7dfe821d3a17f93f2634497770232486155c9c881321c4d22c\ngc:\n");
+            };
+            "root" = define_file! {};
+            "home" = define_file! {
+                "log" = embed_file!("");
+            };
+            "bin" = define_file! {
+                "gcsh" = define_file!("programs/gcsh.gc");
+                "systemctl" = define_file!("programs/systemctl.gc");
+
+                "bash" = define_file!("programs/bash.gc");
+
+                "neofetch" = define_file!("programs/neofetch.gc");
+                "sl" = define_file!("programs/sl.gc");
+
+                "clear" = embed_file!("screen_buffer = \"\";");
+                "help" = define_file!("programs/help.gc");
+                "base64" = define_file!("programs/base64.gc");
+                "sha256sum" = define_file!("programs/sha256sum.gc");
+                "grep" = define_file!("programs/grep.gc");
+                "ls" = define_file!("programs/ls.gc");
+                "cd" = define_file!("programs/cd.gc");
+                "chmod" = define_file!("programs/chmod.gc");
+                "chown" = define_file!("programs/chown.gc");
+                "stat" = define_file!("programs/stat.gc");
+                "whoami" = embed_file!("println(whoami());");
+                "su" = define_file!("programs/su.gc");
+                "sudo" = define_file!("programs/sudo.gc");
+                "ln" = define_file!("programs/ln.gc");
+                "readlink" = define_file!("programs/readlink.gc");
+                "pwd" = embed_file!("println(fs_cwd());");
+                "edit" = define_file!("programs/edit.gc");
+                "diff" = define_file!("programs/diff.gc");
+                "restore" = define_file!("programs/restore.gc");
+                "factory-reset" = define_file!("programs/factory-reset.gc");
+                "snapshot" = define_file!("programs/snapshot.gc");
+                "rollback" = define_file!("programs/rollback.gc");
+            };
+            "lib" = define_file! {
+                "curses.gc" = define_file!("programs/curses.gc");
+            };
+            "firewall" = define_file! {
+                "whitelist" = embed_file!("Nullptr");
+            };
+            "rsa" = define_file! {
+                "keys" = embed_file!("SGVsbG8sIFdvcmxkIQ==\nSGVsbG8sIFRoZXJlIQ==\n");
+            };
+        };
+
+        let mut vfs = Self::default();
+        vfs.import("/", &files)
+            .expect("Failed to build the builtin filesystem!");
+        for program in vfs.list("/bin").unwrap() {
+            vfs.chmod(&format!("/bin/{}", program), 0o755).unwrap();
+        }
+        for shell in ["fish", "zsh", "sh"] {
+            vfs.symlink("bash", &format!("/bin/{}", shell)).unwrap();
+        }
+        // * Anyone can write the whitelist, that's how the player gets past the firewall
+        vfs.chmod("/firewall/whitelist", 0o666).unwrap();
+        vfs.chmod("/etc/shadow", 0o600).unwrap();
+        vfs.chmod("/root", 0o700).unwrap();
+        for path in ["/home", "/home/log"] {
+            vfs.chown(path, Some("gc"), Some("gc")).unwrap();
+        }
+
+        vfs.switch_user("gc")
+            .expect("The builtin filesystem has no garbage collector!");
+        vfs.set_working_directory("/home")
+            .expect("The builtin filesystem has no home directory!");
+        vfs
+    }
+}
+
+/// Resolves `start..end` of a slice, negative indices count from the end.
+fn slice_range(len: usize, start: i32, end: Option<i32>) -> Result<std::ops::Range<usize>> {
+    let resolve = |index: i32| {
+        if index < 0 {
+            index + len as i32
+        } else {
+            index
+        }
+    };
+    let (start, end) = (resolve(start), end.map_or(len as i32, resolve));
+    if start < 0 || end > len as i32 || start > end {
+        bail!(late effect "Slice is out of bounds! Range: {}..{}", start, end);
+    }
+    Ok(start as _..end as _)
+}
+
+fn padding(value: &str, width: i32, fill: Option<String>, usage: &str) -> Result<String> {
+    let fill = fill.unwrap_or_else(|| String::from(" "));
+    let mut chars = fill.chars();
+    let (Some(fill), None) = (chars.next(), chars.next()) else {
+        bail!("{}", usage);
+    };
+    let count = (width.max(0) as usize).saturating_sub(value.chars().count());
+    ensure_string_len(
+        count
+            .checked_mul(fill.len_utf8())
+            .and_then(|len| len.checked_add(value.len())),
+    )?;
+    Ok(std::iter::repeat(fill).take(count).collect())
+}
+
+fn ensure_string_len(len: Option<usize>) -> Result<()> {
+    match len {
+        Some(len) if len <= MAX_STRING_LEN => Ok(()),
+        _ => bail!(late effect "String is too long! Limit: {} bytes", MAX_STRING_LEN),
+    }
+}
+
+/// Stable bottom-up merge sort, `compare` returns a negative number if the first value goes first.
+/// Unlike `slice::sort_by`, it doesn't mind inconsistent orders and stops at the first error.
+fn merge_sort(
+    caller: &mut Caller,
+    mut array: Vec<Value>,
+    compare: &Function,
+) -> Result<Vec<Value>> {
+    let mut buffer = Vec::with_capacity(array.len());
+    let mut width = 1;
+    while width < array.len() {
+        buffer.clear();
+        for start in (0..array.len()).step_by(width * 2) {
+            let middle = (start + width).min(array.len());
+            let end = (start + width * 2).min(array.len());
+            let (mut left, mut right) = (start, middle);
+            while left < middle && right < end {
+                let args = vec![array[left].clone(), array[right].clone()];
+                match caller.call(compare, args)? {
+                    Value::Int(order) if order > 0 => {
+                        buffer.push(array[right].clone());
+                        right += 1;
+                    }
+                    Value::Int(_) => {
+                        buffer.push(array[left].clone());
+                        left += 1;
+                    }
+                    _ => bail!("Usage: {}", SORT_USAGE),
+                }
+            }
+            buffer.extend_from_slice(&array[left..middle]);
+            buffer.extend_from_slice(&array[right..end]);
+        }
+        std::mem::swap(&mut array, &mut buffer);
+        width *= 2;
+    }
+    Ok(array)
+}
+
+/// Calls a predicate callback, which has to return a bool.
+fn test(caller: &mut Caller, predicate: &Function, args: Vec<Value>, usage: &str) -> Result<bool> {
+    match caller.call(predicate, args)? {
+        Value::Bool(result) => Ok(result),
+        _ => bail!("Usage: {}", usage),
+    }
+}
+
+/// Merges two tables, `policy` decides what happens when both have the same key:
+/// `"replace"`, `"keep"`, `"deep"` (merges nested tables), `"error"` or a function
+/// taking the key and both values.
+fn merge(
+    caller: &mut Caller,
+    mut lhs: BTreeMap<Value, Value>,
+    rhs: BTreeMap<Value, Value>,
+    policy: &Value,
+) -> Result<BTreeMap<Value, Value>> {
+    for (key, value) in rhs {
+        let Some(old) = lhs.remove(&key) else {
+            lhs.insert(key, value);
+            continue;
+        };
+        let value = match (policy, old, value) {
+            (Value::String(name), _, value) if name == "replace" => value,
+            (Value::String(name), old, _) if name == "keep" => old,
+            (Value::String(name), Value::Table(old), Value::Table(value)) if name == "deep" => {
+                Value::Table(merge(caller, old, value, policy)?)
+            }
+            (Value::String(name), _, value) if name == "deep" => value,
+            (Value::String(name), _, _) if name == "error" => {
+                bail!(late effect "Merge conflict! Key: {}", key.to_string())
+            }
+            (Value::Function(resolve), old, value) => {
+                caller.call(resolve, vec![key.clone(), old, value])?
+            }
+            _ => bail!(
+                r#"Usage: merge(lhs, rhs, "replace") or merge(lhs, rhs, "keep"/"deep"/"error") or merge(lhs, rhs, fn (key: Any, lhs: Any, rhs: Any) {{ return rhs; }})"#
+            ),
+        };
+        lhs.insert(key, value);
+    }
+    Ok(lhs)
+}
+
+fn regex(pattern: &str) -> Result<pattern::Regex> {
+    pattern::Regex::new(pattern)
+        .or_else(|error| bail!(late effect "Invalid regex '{}'! {}", pattern, error))
+}
+
+fn group(text: &[char], captures: &pattern::Captures, index: usize) -> String {
+    match captures.get(index) {
+        Some(Some(range)) => text[range.clone()].iter().collect(),
+        _ => String::new(),
+    }
+}
+
+/// What `fs_stat` returns.
+fn stat_table(stat: vfs::Stat) -> Value {
+    let kind = match stat.kind {
+        "directory" => 'd',
+        "symlink" => 'l',
+        _ => '-',
+    };
+    let metadata = stat.metadata;
+    let modified = metadata.modified.as_secs().min(i32::MAX as u64);
+    Value::Table(btree_map! {
+        Value::String(String::from("inode")) => Value::Int(stat.inode as _),
+        Value::String(String::from("type")) => Value::String(String::from(stat.kind)),
+        Value::String(String::from("size")) => Value::Int(stat.size as _),
+        Value::String(String::from("links")) => Value::Int(stat.links as _),
+        Value::String(String::from("owner")) => Value::String(metadata.owner),
+        Value::String(String::from("group")) => Value::String(metadata.group),
+        Value::String(String::from("mode")) => Value::Int(metadata.mode as _),
+        Value::String(String::from("permissions")) => Value::String(format!("{}{}", kind, metadata.permissions())),
+        Value::String(String::from("modified")) => Value::Int(modified as _),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calls a std function, errors and `exception` effects become their message.
+    fn call(name: &str, args: Vec<Value>) -> std::result::Result<Value, String> {
+        call_in(&mut Scopes::default(), name, args)
+    }
+
+    fn call_in(
+        scopes: &mut Scopes,
+        name: &str,
+        args: Vec<Value>,
+    ) -> std::result::Result<Value, String> {
+        let mut library = Library::with_std();
+        let function = library.functions[name].clone();
+        let mut caller = Caller {
+            scopes,
+            library: &mut library,
+        };
+        function(&mut caller, args).map_err(|exception| match exception {
+            Exception::Effect(effect) => effect.args()[0].to_string(),
+            exception => exception.message().unwrap_or_default(),
+        })
+    }
+
+    fn string(value: &str) -> Value {
+        Value::String(value.to_owned())
+    }
+
+    fn strings(values: &[&str]) -> Value {
+        Value::Array(values.iter().map(|value| string(value)).collect())
+    }
+
+    #[test]
+    fn split() {
+        let value = call("split", vec![string("a,b,,c"), string(",")]);
+        assert_eq!(value.unwrap(), strings(&["a", "b", "", "c"]));
+        let value = call("split", vec![string("  a  b\tc ")]);
+        assert_eq!(value.unwrap(), strings(&["a", "b", "c"]));
+        assert_eq!(call("split", vec![string("")]).unwrap(), strings(&[]));
+        let error = call("split", vec![string("a b"), string("")]).unwrap_err();
+        assert!(error.starts_with("Usage: split("), "{}", error);
+    }
+
+    #[test]
+    fn join() {
+        let values = Value::Array(vec![string("a"), Value::Int(1), Value::Bool(true)]);
+        let value = call("join", vec![values, string(", ")]);
+        assert_eq!(value.unwrap(), string("a, 1, true"));
+        let value = call("join", vec![strings(&[]), string("-")]);
+        assert_eq!(value.unwrap(), string(""));
+        let value = call("join", vec![strings(&["a", "b"]), string("")]);
+        assert_eq!(value.unwrap(), string("ab"));
+    }
+
+    #[test]
+    fn substring() {
+        let value = call(
+            "substring",
+            vec![string("hello"), Value::Int(1), Value::Int(3)],
+        );
+        assert_eq!(value.unwrap(), string("el"));
+        let value = call("substring", vec![string("hello"), Value::Int(-3)]);
+        assert_eq!(value.unwrap(), string("llo"));
+        let value = call("substring", vec![string("hello"), Value::Int(5)]);
+        assert_eq!(value.unwrap(), string(""));
+        for (start, end) in [(0, 6), (-6, 5), (3, 2)] {
+            let args = vec![string("hello"), Value::Int(start), Value::Int(end)];
+            let error = call("substring", args).unwrap_err();
+            assert!(error.starts_with("Slice is out of bounds!"), "{}", error);
+        }
+        let error = call("substring", vec![string("é"), Value::Int(1)]).unwrap_err();
+        assert!(
+            error.starts_with("Substring splits a character!"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn index_of_and_find() {
+        let array = Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(2)]);
+        let value = call("index_of", vec![array.clone(), Value::Int(2)]);
+        assert_eq!(value.unwrap(), Value::Int(1));
+        assert_eq!(
+            call("index_of", vec![array, Value::Int(3)]).unwrap(),
+            Value::Int(-1)
+        );
+        assert_eq!(
+            call("find", vec![string("abcabc"), string("c")]).unwrap(),
+            Value::Int(2)
+        );
+        assert_eq!(
+            call("rfind", vec![string("abcabc"), string("c")]).unwrap(),
+            Value::Int(5)
+        );
+        assert_eq!(
+            call("find", vec![string("abc"), string("d")]).unwrap(),
+            Value::Int(-1)
+        );
+    }
+
+    #[test]
+    fn replace() {
+        let value = call("replace", vec![string("a-b-c"), string("-"), string("+")]);
+        assert_eq!(value.unwrap(), string("a+b+c"));
+        let args = vec![string("a-b-c"), string("-"), string(""), Value::Int(1)];
+        assert_eq!(call("replace", args).unwrap(), string("ab-c"));
+        let args = vec![string("a-b-c"), string("-"), string("+"), Value::Int(-1)];
+        assert_eq!(call("replace", args).unwrap(), string("a-b-c"));
+        let error = call("replace", vec![string("abc"), string(""), string("+")]).unwrap_err();
+        assert!(error.starts_with("Usage: replace("), "{}", error);
+    }
+
+    #[test]
+    fn trim_and_case() {
+        assert_eq!(
+            call("trim", vec![string(" \t a b \n")]).unwrap(),
+            string("a b")
+        );
+        assert_eq!(call("trim", vec![string("   ")]).unwrap(), string(""));
+        assert_eq!(call("upper", vec![string("aBc")]).unwrap(), string("ABC"));
+        assert_eq!(call("lower", vec![string("aBc")]).unwrap(), string("abc"));
+    }
+
+    #[test]
+    fn parse_int() {
+        assert_eq!(
+            call("parse_int", vec![string(" 42 ")]).unwrap(),
+            Value::Int(42)
+        );
+        assert_eq!(
+            call("parse_int", vec![string("-7")]).unwrap(),
+            Value::Int(-7)
+        );
+        let value = call("parse_int", vec![string("ff"), Value::Int(16)]);
+        assert_eq!(value.unwrap(), Value::Int(255));
+        for value in ["", "abc", "1.5", "99999999999"] {
+            let error = call("parse_int", vec![string(value)]).unwrap_err();
+            assert_eq!(error, format!("Failed to parse '{}' as int!", value));
+        }
+        let error = call("parse_int", vec![string("1"), Value::Int(1)]).unwrap_err();
+        assert!(error.starts_with("Usage: parse_int("), "{}", error);
+    }
+
+    #[test]
+    fn usage_on_wrong_arguments() {
+        let error = call("split", vec![Value::Int(1)]).unwrap_err();
+        assert_eq!(error, "Usage: split(String, [String]) -> Array");
+        let error = call("trim", vec![]).unwrap_err();
+        assert_eq!(error, "Usage: trim(String) -> String");
+        let error = call(
+            "substring",
+            vec![string("a"), Value::Int(0), Value::Int(1), Value::Int(2)],
+        );
+        assert_eq!(
+            error.unwrap_err(),
+            "Usage: substring(String, int, [int]) -> String"
+        );
+    }
+
+    /// Draws numbers, a shuffled array and a choice after seeding a fresh `Scopes`.
+    fn random_sequence(seed: i32) -> Vec<Value> {
+        let mut scopes = Scopes::default();
+        call_in(&mut scopes, "seed", vec![Value::Int(seed)]).unwrap();
+        let mut sequence = (0..20)
+            .map(|_| call_in(&mut scopes, "random", vec![Value::Int(1000)]).unwrap())
+            .collect::<Vec<_>>();
+        let array = Value::Array((0..10).map(Value::Int).collect());
+        sequence.push(call_in(&mut scopes, "shuffle", vec![array.clone()]).unwrap());
+        sequence.push(call_in(&mut scopes, "choose", vec![array]).unwrap());
+        sequence
+    }
+
+    #[test]
+    fn seeded_random_is_reproducible() {
+        assert_eq!(random_sequence(42), random_sequence(42));
+        assert_eq!(random_sequence(-1), random_sequence(-1));
+        assert_ne!(random_sequence(42), random_sequence(43));
+    }
+
+    #[test]
+    fn containers() {
+        assert_eq!(call("len", vec![string("é")]).unwrap(), Value::Int(2));
+        let array = Value::Array(vec![Value::Int(1), Value::Int(2)]);
+        assert_eq!(call("len", vec![array.clone()]).unwrap(), Value::Int(2));
+        assert_eq!(call("pop", vec![string("aé")]).unwrap(), string("a"));
+        assert_eq!(call("pop", vec![string("")]).unwrap(), string(""));
+        let popped = call("pop", vec![array.clone()]).unwrap();
+        assert_eq!(popped, Value::Array(vec![Value::Int(1)]));
+        let value = call("remove", vec![string("aéb"), Value::Int(1)]);
+        assert_eq!(value.unwrap(), string("ab"));
+        let error = call("remove", vec![string("é"), Value::Int(1)]).unwrap_err();
+        assert_eq!(error, "Remove index splits a character! Index: 1");
+        let error = call("remove", vec![array.clone(), Value::Int(2)]).unwrap_err();
+        assert_eq!(error, "Remove index is out of bounds! Index: 2");
+        let value = call("contains", vec![array, Value::Int(2)]);
+        assert_eq!(value.unwrap(), Value::Bool(true));
+        let value = call("contains", vec![string("text"), string("ex")]);
+        assert_eq!(value.unwrap(), Value::Bool(true));
+        let error = call("len", vec![Value::Int(1)]).unwrap_err();
+        assert!(error.starts_with("Usage: len("), "{}", error);
+        let error = call("len", vec![]).unwrap_err();
+        assert_eq!(error, "Usage: len(Any) -> int");
+    }
+
+    #[test]
+    fn prefixes_and_lines() {
+        let value = call("starts_with", vec![string("garbage"), string("garb")]);
+        assert_eq!(value.unwrap(), Value::Bool(true));
+        let value = call("starts_with", vec![string("garbage"), string("age")]);
+        assert_eq!(value.unwrap(), Value::Bool(false));
+        let value = call("ends_with", vec![string("garbage"), string("age")]);
+        assert_eq!(value.unwrap(), Value::Bool(true));
+        let value = call("ends_with", vec![string("garbage"), string("")]);
+        assert_eq!(value.unwrap(), Value::Bool(true));
+        let value = call("lines", vec![string("a\nb\r\n\nc\n")]);
+        assert_eq!(value.unwrap(), strings(&["a", "b", "", "c"]));
+        assert_eq!(call("lines", vec![string("")]).unwrap(), strings(&[]));
+    }
+
+    #[test]
+    fn repeat_and_padding() {
+        let value = call("repeat", vec![string("ab"), Value::Int(3)]);
+        assert_eq!(value.unwrap(), string("ababab"));
+        let value = call("repeat", vec![string("ab"), Value::Int(0)]);
+        assert_eq!(value.unwrap(), string(""));
+        let error = call("repeat", vec![string("-"), Value::Int(-1)]).unwrap_err();
+        assert_eq!(error, r#"Usage: repeat("-", 10)"#);
+        let error = call("repeat", vec![string("ab"), Value::Int(i32::MAX)]).unwrap_err();
+        assert_eq!(error, "String is too long! Limit: 1048576 bytes");
+
+        let value = call("pad_left", vec![string("7"), Value::Int(3), string("0")]);
+        assert_eq!(value.unwrap(), string("007"));
+        let value = call("pad_left", vec![string("é"), Value::Int(3)]);
+        assert_eq!(value.unwrap(), string("  é"));
+        let value = call("pad_right", vec![string("ab"), Value::Int(4), string("é")]);
+        assert_eq!(value.unwrap(), string("abéé"));
+        let value = call("pad_right", vec![string("long"), Value::Int(2)]);
+        assert_eq!(value.unwrap(), string("long"));
+        let error = call("pad_left", vec![string("7"), Value::Int(3), string("00")]);
+        assert_eq!(error.unwrap_err(), r#"Usage: pad_left("7", 3, "0")"#);
+        let error = call("pad_right", vec![string("a"), Value::Int(i32::MAX)]);
+        assert_eq!(
+            error.unwrap_err(),
+            "String is too long! Limit: 1048576 bytes"
+        );
+    }
+
+    #[test]
+    fn char_codes() {
+        // Indices are byte offsets, like everywhere else in strings
+        let value = call("char_code", vec![string("aé")]);
+        assert_eq!(value.unwrap(), Value::Int('a' as i32));
+        let value = call("char_code", vec![string("aé"), Value::Int(1)]);
+        assert_eq!(value.unwrap(), Value::Int('é' as i32));
+        let error = call("char_code", vec![string("aé"), Value::Int(2)]).unwrap_err();
+        assert_eq!(error, "Char code index is out of bounds! Index: 2");
+        let error = call("char_code", vec![string("a"), Value::Int(-1)]).unwrap_err();
+        assert_eq!(error, "Char code index is out of bounds! Index: -1");
+
+        let value = call("from_char_code", vec![Value::Int(104), Value::Int(233)]);
+        assert_eq!(value.unwrap(), string("hé"));
+        assert_eq!(call("from_char_code", vec![]).unwrap(), string(""));
+        let error = call("from_char_code", vec![Value::Int(0xD800)]).unwrap_err();
+        assert_eq!(error, "Invalid char code! Code: 55296");
+    }
+
+    /// Runs `code` and returns its `result` global.
+    fn eval(code: &str) -> std::result::Result<Value, String> {
+        let (mut scopes, mut library) = (Scopes::default(), Library::with_std());
+        let program = crate::Program::parse(code).unwrap();
+        match program.eval(&mut scopes, &mut library) {
+            std::result::Result::Ok(()) => Ok(scopes.get_global("result").unwrap().clone()),
+            Err(Exception::Effect(effect)) => Err(effect.args()[0].to_string()),
+            Err(exception) => Err(exception.message().unwrap_or_default()),
+        }
+    }
+
+    #[test]
+    fn sort() {
+        let numbers =
+            |values: &[i32]| Value::Array(values.iter().copied().map(Value::Int).collect());
+        let value = call("sort", vec![numbers(&[3, 1, 2])]);
+        assert_eq!(value.unwrap(), numbers(&[1, 2, 3]));
+        let value =
+            eval("global result = sort([3, 1, 5, 2, 4], fn (a: int, b: int) { return b - a; });");
+        assert_eq!(value.unwrap(), numbers(&[5, 4, 3, 2, 1]));
+        // * Equal elements keep their order
+        let value = eval(
+            r#"global result = sort(["bb", "a", "cc", "b"], fn (a: String, b: String) { return len(a) - len(b); });"#,
+        );
+        assert_eq!(value.unwrap(), strings(&["a", "b", "bb", "cc"]));
+
+        let value = eval(
+            "global result = sort(range(100), fn (a: int, b: int) { return random(3) - 1; });",
+        );
+        let Value::Array(mut value) = value.unwrap() else {
+            panic!("Sort didn't return an array");
+        };
+        value.sort();
+        assert_eq!(
+            Value::Array(value),
+            Value::Array((0..100).map(Value::Int).collect())
+        );
+
+        let error = eval(
+            r#"
+                global result = sort([3, 1, 2], fn (a: int, b: int) {
+                    return remove([], a);
+                });
+            "#,
+        );
+        assert_eq!(
+            error.unwrap_err(),
+            "Remove index is out of bounds! Index: 3"
+        );
+        let error = eval(r#"global result = sort([2, 1], fn (a: int, b: int) { return "a"; });"#);
+        assert_eq!(error.unwrap_err(), format!("Usage: {}", SORT_USAGE));
+    }
+
+    #[test]
+    fn range() {
+        let value = call("range", vec![Value::Int(3)]).unwrap();
+        assert_eq!(value, Value::Array((0..3).map(Value::Int).collect()));
+        let value = call("range", vec![Value::Int(5), Value::Int(0), Value::Int(-2)]).unwrap();
+        assert_eq!(value, Value::Array([5, 3, 1].map(Value::Int).to_vec()));
+        let value = call(
+            "range",
+            vec![
+                Value::Int(i32::MAX - 1),
+                Value::Int(i32::MAX),
+                Value::Int(5),
+            ],
+        );
+        assert_eq!(value.unwrap(), Value::Array(vec![Value::Int(i32::MAX - 1)]));
+        let error = call("range", vec![Value::Int(i32::MAX)]).unwrap_err();
+        assert_eq!(error, "Range is too long! Limit: 1048576 values");
+    }
+
+    #[test]
+    fn devices() {
+        let jumps = std::cell::Cell::new(1);
+        let mut library = Library::with_std();
+        library.add_device("level", |_| Ok(String::from("3")));
+        library.add_writable_device(
+            "jumps",
+            |_| Ok(jumps.get().to_string()),
+            |_, content| match content.trim().parse() {
+                std::result::Result::Ok(value) => {
+                    jumps.set(value);
+                    Ok(())
+                }
+                Err(_) => bail!(late effect "Max jumps is a number!"),
+            },
+        );
+        let mut scopes = Scopes::default();
+        *scopes.vfs_mut() = Vfs::builtin();
+        let vfs = scopes.vfs_mut();
+        vfs.bind("/sys/level/index", "level", 0o444).unwrap();
+        vfs.bind("/sys/level/name", "level", 0o666).unwrap();
+        vfs.bind("/sys/level/missing", "missing", 0o444).unwrap();
+        vfs.bind("/proc/player/max_jumps", "jumps", 0o666).unwrap();
+
+        let mut run = |name: &str, args: Vec<Value>| {
+            let function = library.functions[name].clone();
+            let mut caller = Caller {
+                scopes: &mut scopes,
+                library: &mut library,
+            };
+            function(&mut caller, args).map_err(|exception| match exception {
+                Exception::Effect(effect) => effect.args()[0].to_string(),
+                exception => exception.message().unwrap_or_default(),
+            })
+        };
+        let read = run("fs_read", vec![string("/sys/level/index")]);
+        assert_eq!(read.unwrap(), string("3"));
+        let written = run(
+            "fs_write",
+            vec![string("/proc/player/max_jumps"), string("2")],
+        );
+        assert!(written.is_ok());
+        let read = run("fs_read", vec![string("/proc/player/max_jumps")]);
+        assert_eq!(read.unwrap(), string("2"));
+        let error = run(
+            "fs_write",
+            vec![string("/proc/player/max_jumps"), string("x")],
+        );
+        assert_eq!(error.unwrap_err(), "Max jumps is a number!");
+
+        let error = run("fs_write", vec![string("/sys/level/index"), string("4")]);
+        assert_eq!(
+            error.unwrap_err(),
+            "Permission denied! Path: '/sys/level/index'"
+        );
+        let error = run("fs_write", vec![string("/sys/level/name"), string("4")]);
+        assert_eq!(
+            error.unwrap_err(),
+            "Read-only file! Path: '/sys/level/name'"
+        );
+        let error = run("fs_read", vec![string("/sys/level/missing")]);
+        assert_eq!(
+            error.unwrap_err(),
+            "Device isn't connected! Path: '/sys/level/missing'"
+        );
+        drop(run);
+
+        assert_eq!(jumps.get(), 2);
+        // * Nothing is stored in the files themselves
+        assert_eq!(scopes.vfs().file("/proc/player/max_jumps"), Some(""));
+    }
+}
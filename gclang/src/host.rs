@@ -0,0 +1,277 @@
+//! Typed host functions: conversions between [`Value`] and Rust types, so that
+//! [`Library::register`] can check arguments and generate usage messages itself.
+use super::executor::{Caller, Function, Library, Result, Value};
+use std::collections::BTreeMap;
+
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Option<Self>;
+
+    /// The value of a parameter that wasn't passed, `None` if it's required.
+    fn missing() -> Option<Self> {
+        None
+    }
+
+    /// Name of the type in signatures, matching gclang argument types where possible.
+    fn type_name() -> String;
+}
+
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+
+    fn type_name() -> String;
+}
+
+macro_rules! impl_value {
+    ($($type: ty => $variant: ident $name: literal;)+) => {
+        $(
+            impl FromValue for $type {
+                fn from_value(value: Value) -> Option<Self> {
+                    match value {
+                        Value::$variant(value) => Some(value),
+                        _ => None,
+                    }
+                }
+
+                fn type_name() -> String {
+                    String::from($name)
+                }
+            }
+
+            impl IntoValue for $type {
+                fn into_value(self) -> Value {
+                    Value::$variant(self)
+                }
+
+                fn type_name() -> String {
+                    String::from($name)
+                }
+            }
+        )+
+    };
+}
+
+impl_value! {
+    i32 => Int "int";
+    bool => Bool "bool";
+    String => String "String";
+    Function => Function "fn";
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Option<Self> {
+        Some(value)
+    }
+
+    fn type_name() -> String {
+        String::from("Any")
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+
+    fn type_name() -> String {
+        String::from("Any")
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Array(array) => array.into_iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+
+    fn type_name() -> String {
+        String::from("Array")
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::Array(self.into_iter().map(IntoValue::into_value).collect())
+    }
+
+    fn type_name() -> String {
+        String::from("Array")
+    }
+}
+
+impl<K: FromValue + Ord, V: FromValue> FromValue for BTreeMap<K, V> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Table(table) => table
+                .into_iter()
+                .map(|(key, value)| Some((K::from_value(key)?, V::from_value(value)?)))
+                .collect(),
+            _ => None,
+        }
+    }
+
+    fn type_name() -> String {
+        String::from("Table")
+    }
+}
+
+impl<K: IntoValue, V: IntoValue> IntoValue for BTreeMap<K, V> {
+    fn into_value(self) -> Value {
+        Value::Table(
+            self.into_iter()
+                .map(|(key, value)| (key.into_value(), value.into_value()))
+                .collect(),
+        )
+    }
+
+    fn type_name() -> String {
+        String::from("Table")
+    }
+}
+
+/// `unit` is `None`, so optional values can be passed and returned.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Unit => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+
+    fn type_name() -> String {
+        format!("[{}]", T::type_name())
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        match self {
+            Some(value) => value.into_value(),
+            None => Value::Unit,
+        }
+    }
+
+    fn type_name() -> String {
+        format!("[{}]", T::type_name())
+    }
+}
+
+impl IntoValue for usize {
+    fn into_value(self) -> Value {
+        Value::Int(self as _)
+    }
+
+    fn type_name() -> String {
+        String::from("int")
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Unit
+    }
+
+    fn type_name() -> String {
+        String::from("Unit")
+    }
+}
+
+// * ---------------------------------- Parameters ---------------------------------- * //
+/// Takes all the remaining arguments, so it has to be the last parameter.
+pub struct Variadic<T>(pub Vec<T>);
+
+/// A single parameter of a host function. `None` means the arguments don't fit it.
+pub trait FromArgs: Sized {
+    fn from_args(args: &mut std::vec::IntoIter<Value>) -> Option<Self>;
+    fn describe() -> String;
+}
+
+impl<T: FromValue> FromArgs for T {
+    fn from_args(args: &mut std::vec::IntoIter<Value>) -> Option<Self> {
+        match args.next() {
+            Some(value) => T::from_value(value),
+            None => T::missing(),
+        }
+    }
+
+    fn describe() -> String {
+        T::type_name()
+    }
+}
+
+impl<T: FromValue> FromArgs for Variadic<T> {
+    fn from_args(args: &mut std::vec::IntoIter<Value>) -> Option<Self> {
+        args.map(T::from_value).collect::<Option<_>>().map(Variadic)
+    }
+
+    fn describe() -> String {
+        format!("{}...", T::type_name())
+    }
+}
+
+// * ------------------------------------ Functions ----------------------------------- * //
+/// Implemented for closures taking `&mut Caller` followed by up to six parameters.
+pub trait HostFunction<Args> {
+    /// Returns `None` if the arguments don't match the signature.
+    fn call(&self, caller: &mut Caller, args: Vec<Value>) -> Option<Result<Value>>;
+    fn signature(name: &str) -> String;
+}
+
+macro_rules! impl_host_function {
+    ($($arg: ident $value: ident),*) => {
+        impl<F, R, $($arg,)*> HostFunction<($($arg,)*)> for F
+        where
+            F: Fn(&mut Caller, $($arg),*) -> Result<R>,
+            R: IntoValue,
+            $($arg: FromArgs,)*
+        {
+            #[allow(unused_mut)]
+            fn call(&self, caller: &mut Caller, args: Vec<Value>) -> Option<Result<Value>> {
+                let mut args = args.into_iter();
+                $(let $value = $arg::from_args(&mut args)?;)*
+                if args.next().is_some() {
+                    return None;
+                }
+                Some(self(caller, $($value),*).map(IntoValue::into_value))
+            }
+
+            fn signature(name: &str) -> String {
+                let args: &[String] = &[$($arg::describe()),*];
+                format!("{}({}) -> {}", name, args.join(", "), R::type_name())
+            }
+        }
+    };
+}
+
+impl_host_function!();
+impl_host_function!(A a);
+impl_host_function!(A a, B b);
+impl_host_function!(A a, B b, C c);
+impl_host_function!(A a, B b, C c, D d);
+impl_host_function!(A a, B b, C c, D d, E e);
+impl_host_function!(A a, B b, C c, D d, E e, G g);
+
+impl<'a> Library<'a> {
+    /// Registers a typed host function. Arguments are converted and checked before
+    /// the call, and a mismatch fails with the generated signature as usage.
+    pub fn register<Args, F: HostFunction<Args> + 'a>(&mut self, name: &str, function: F) {
+        let signature = F::signature(name);
+        let usage = signature.clone();
+        self.add_function(name, move |caller, args| {
+            match HostFunction::call(&function, caller, args) {
+                Some(result) => result,
+                None => crate::bail!("Usage: {}", usage),
+            }
+        });
+        self.signatures.insert(name.to_owned(), signature);
+    }
+
+    pub fn signature(&self, name: &str) -> Option<&str> {
+        self.signatures.get(name).map(String::as_str)
+    }
+}
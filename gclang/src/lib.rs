@@ -0,0 +1,94 @@
+//! GCLang, the scripting language of Garbage Collector 2.
+//!
+//! ```
+//! use gclang::{Caller, Library, Program, Scopes, Value};
+//!
+//! let mut scopes = Scopes::default();
+//! let mut library = Library::with_std();
+//! library.register("shout", |_: &mut Caller, text: String| {
+//!     gclang::Ok(text.to_uppercase())
+//! });
+//!
+//! let program = Program::parse(
+//!     r#"
+//!     global answer = 42;
+//!     global greeting = shout("hello");
+//!     global double = fn (x: int) { return x * 2; };
+//!     "#,
+//! )
+//! .expect("Failed to parse!");
+//! assert!(program.eval(&mut scopes, &mut library).is_ok());
+//! assert_eq!(scopes.get_global("answer"), Some(&Value::Int(42)));
+//! assert_eq!(
+//!     scopes.get_global("greeting"),
+//!     Some(&Value::String(String::from("HELLO")))
+//! );
+//!
+//! // * gclang functions can be called back from the host
+//! let Some(Value::Function(double)) = scopes.get_global("double").cloned() else {
+//!     panic!("double isn't a function!");
+//! };
+//! let result = double.call(&mut scopes, &mut library, vec![Value::Int(21)]);
+//! assert!(matches!(result, Ok(Value::Int(42))));
+//! ```
+//!
+//! [`Program`] is parsed once and evaluated against [`Scopes`], which hold the
+//! global state between evaluations. Host functions live in a [`Library`], usually added
+//! with [`Library::register`] from closures with typed parameters, and
+//! the syntax tree can be inspected through the [`parser`] module.
+mod clock;
+mod data;
+mod encoding;
+mod executor;
+mod format;
+mod gcstd;
+mod host;
+pub mod parser;
+mod pattern;
+pub mod storage;
+pub mod vfs;
+
+pub use anyhow::{anyhow, Context, Error};
+pub use clock::Clock;
+pub use executor::Library;
+pub use executor::Scopes;
+pub use executor::{bail, ensure, Effect, Exception, Ok, Result};
+pub use executor::{Arguments, Caller, Device, Function, Intrinsic, LibFunction, Value};
+pub use format::format;
+pub use host::{FromArgs, FromValue, HostFunction, IntoValue, Variadic};
+pub use parser::Program;
+pub use storage::{FileStorage, MemoryStorage, Storage};
+pub use vfs::{Mount, Vfs};
+
+#[macro_export]
+macro_rules! library_function {
+    ($library: ident += $name: ident ($scopes: ident, $args: ident) $function: block) => {
+        $library.add_function(
+            stringify!($name),
+            |caller: &mut $crate::Caller, $args: Vec<$crate::Value>| {
+                let $scopes = &mut *caller.scopes;
+                $function
+            },
+        );
+    };
+}
+
+pub use library_function;
+
+#[macro_export]
+macro_rules! ensure_type {
+    ($value: expr, $type: ident, $err: literal) => {
+        if let $crate::Value::$type(value) = $value {
+            value
+        } else {
+            $crate::bail!($err);
+        }
+    };
+}
+
+pub use ensure_type;
+
+// * ----------------------------------- Programs ----------------------------------- * //
+pub fn gcsh() -> Program {
+    Program::parse(include_str!("programs/gcsh.gc")).expect("Failed to compile gcsh!")
+}
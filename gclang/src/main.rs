@@ -0,0 +1,214 @@
+use gclang::{Caller, Library, Mount, Program, Scopes, Value, Variadic};
+use std::io::{BufRead, Write};
+
+fn main() {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut scopes = Scopes::default();
+    if let Ok(epoch) = std::time::UNIX_EPOCH.elapsed() {
+        scopes.clock_mut().set_epoch(epoch);
+    }
+    for mount in Mount::from_args(&mut args) {
+        if let Err(error) = scopes.vfs_mut().mount(mount) {
+            print_error(&error.message().unwrap_or_default());
+            std::process::exit(1);
+        }
+    }
+    let mut library = library();
+    scopes.set_global(
+        "args",
+        Value::Array(args.iter().cloned().map(Value::String).collect()),
+    );
+
+    if let Some(path) = args.first() {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                print_error(&format!("Failed to read '{}': {}", path, error));
+                std::process::exit(1);
+            }
+        };
+        let program = match Program::parse(&source) {
+            Ok(program) => program,
+            Err(error) => {
+                print_error(&error.to_string());
+                std::process::exit(1);
+            }
+        };
+        let result = program
+            .eval(&mut scopes, &mut library)
+            .and_then(|_| run_timers(&mut scopes, &mut library));
+        if let Some(message) = result.err().and_then(|error| error.message()) {
+            print_error(&message);
+            std::process::exit(1);
+        }
+    } else {
+        repl(&mut scopes, &mut library);
+    }
+}
+
+fn library() -> Library<'static> {
+    let mut library = Library::with_std();
+    library.register("print", |_: &mut Caller, args: Variadic<Value>| {
+        let output = args
+            .0
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        print!("{}", to_ansi(&output));
+        std::io::stdout().flush().ok();
+        gclang::Ok(())
+    });
+    library.register("println", |_: &mut Caller, args: Variadic<Value>| {
+        let output = args
+            .0
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("{}", to_ansi(&output));
+        gclang::Ok(())
+    });
+    library.register(
+        "printf",
+        |_: &mut Caller, template: String, args: Variadic<Value>| {
+            print!("{}", to_ansi(&gclang::format(&template, &args.0)?));
+            std::io::stdout().flush().ok();
+            gclang::Ok(())
+        },
+    );
+    library.register("input", |_: &mut Caller| {
+        let mut line = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(gclang::Error::from)?;
+        gclang::Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+    });
+    library.register("exit", |_: &mut Caller| -> gclang::Result<()> {
+        std::process::exit(0)
+    });
+    library
+}
+
+/// Waits for the timers a script scheduled, until there are none left.
+fn run_timers(scopes: &mut Scopes, library: &mut Library) -> gclang::Result<()> {
+    while let Some(delay) = scopes.clock().until_next_timer() {
+        std::thread::sleep(delay);
+        scopes.advance_clock(library, delay)?;
+    }
+    Ok(())
+}
+
+fn repl(scopes: &mut Scopes, library: &mut Library) {
+    println!("GCLang REPL. Ctrl+D or exit(); to quit.");
+    scopes.push_scope();
+    let mut source = String::new();
+    let mut last_input = std::time::Instant::now();
+    loop {
+        print!("{}", if source.is_empty() { "> " } else { ". " });
+        std::io::stdout().flush().ok();
+
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => source.push_str(&line),
+        }
+
+        // * Keep reading until every bracket is closed, an empty line forces evaluation
+        if unclosed_brackets(&source) > 0 && !line.trim().is_empty() {
+            continue;
+        }
+
+        // * Timers of the REPL only fire between inputs
+        let elapsed = last_input.elapsed();
+        last_input = std::time::Instant::now();
+        if let Some(message) = scopes
+            .advance_clock(library, elapsed)
+            .err()
+            .and_then(|error| error.message())
+        {
+            print_error(&message);
+        }
+
+        match Program::parse(&source) {
+            Ok(program) => {
+                if let Some(message) = program
+                    .import(scopes, library)
+                    .err()
+                    .and_then(|error| error.message())
+                {
+                    print_error(&message);
+                }
+            }
+            Err(error) => print_error(&error.to_string()),
+        }
+        source.clear();
+    }
+    scopes.pop_scope();
+}
+
+fn unclosed_brackets(source: &str) -> i32 {
+    let mut depth = 0;
+    let mut chars = source.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => {
+                while let Some(char) = chars.next() {
+                    match char {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => (),
+                    }
+                }
+            }
+            '/' if chars.clone().next() == Some('/') => {
+                chars.by_ref().find(|char| *char == '\n');
+            }
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => (),
+        }
+    }
+    depth
+}
+
+/// Errors are shown the same way the in-game terminal shows them.
+fn print_error(message: &str) {
+    eprintln!("{}", to_ansi(&format!("\x1bff0000{}\x18", message)));
+}
+
+/// Translates the terminal color escapes (`\x1bRRGGBB`, `\x1cRRGGBB`, `\x18` and `\x19`)
+/// into ANSI ones.
+fn to_ansi(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(['\x1b', '\x1c', '\x18', '\x19']) {
+        output.push_str(&rest[..index]);
+        let escape = rest.as_bytes()[index];
+        rest = &rest[index + 1..];
+        match escape {
+            0x18 => output.push_str("\x1b[39m"),
+            0x19 => output.push_str("\x1b[49m"),
+            _ => {
+                if let Some(color) = rest
+                    .get(..6)
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                {
+                    output.push_str(&format!(
+                        "\x1b[{};2;{};{};{}m",
+                        if escape == 0x1b { 38 } else { 48 },
+                        color >> 16,
+                        (color >> 8) & 0xff,
+                        color & 0xff
+                    ));
+                    rest = &rest[6..];
+                }
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
@@ -1,3 +1,4 @@
+//! Lexer and syntax tree of gclang. Every node is public so tools can walk a parsed [`Program`].
 use laps::ast::{NonEmptySepList, NonEmptySeq, SepSeq};
 use laps::lexer::{int_literal, str_literal};
 use laps::prelude::*;
@@ -10,7 +11,7 @@ use std::{fmt, str::FromStr};
 // * ---------------------------------------------------------------------------------- Lexer --------------------------------------------------------------------------------- * //
 #[token_kind]
 #[derive(Debug, Tokenize)]
-enum TokenKind {
+pub enum TokenKind {
     #[skip(r"\s+|//.+\n")]
     _Skip,
     #[regex(r"global|let|fn|if|else|true|false|unit|return|table|any|with|ctl|effect|resume")]
@@ -32,7 +33,7 @@ enum TokenKind {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Keyword {
+pub enum Keyword {
     Global,
     Let,
     Fn,
@@ -98,7 +99,7 @@ impl fmt::Display for Keyword {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub(super) enum Type {
+pub enum Type {
     Int,
     Bool,
     String,
@@ -137,7 +138,7 @@ impl fmt::Display for Type {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Operator {
+pub enum Operator {
     Add,
     Sub,
     Mul,
@@ -203,7 +204,7 @@ impl fmt::Display for Operator {
 }
 
 #[derive(Clone, Debug, PartialEq)]
-struct StringLiteral(String);
+pub struct StringLiteral(String);
 impl FromStr for StringLiteral {
     type Err = ();
 
@@ -218,11 +219,11 @@ impl fmt::Display for StringLiteral {
     }
 }
 
-type Token = laps::token::Token<TokenKind>;
+pub type Token = laps::token::Token<TokenKind>;
 
 token_ast! {
   #[derive(Clone, Debug, PartialEq)]
-  macro Token<TokenKind> {
+  pub macro Token<TokenKind> {
     [ident] => { kind: TokenKind::Ident(_), prompt: "identifier" },
     [global] => { kind: TokenKind::Keyword(Keyword::Global) },
     [let] => { kind: TokenKind::Keyword(Keyword::Let) },
@@ -271,7 +272,7 @@ token_ast! {
 }
 
 impl Token![ident] {
-    pub(super) fn ident(&self) -> &str {
+    pub fn ident(&self) -> &str {
         match &self.0.kind {
             TokenKind::Ident(value) => value,
             _ => unreachable!(),
@@ -280,7 +281,7 @@ impl Token![ident] {
 }
 
 impl Token![type] {
-    pub(super) fn inner(&self) -> &Type {
+    pub fn inner(&self) -> &Type {
         match &self.0.kind {
             TokenKind::Type(value) => value,
             _ => unreachable!(),
@@ -289,7 +290,7 @@ impl Token![type] {
 }
 
 impl Token![lint] {
-    pub(super) fn inner(&self) -> u64 {
+    pub fn inner(&self) -> u64 {
         match self.0.kind {
             TokenKind::Int(value) => value,
             _ => unreachable!(),
@@ -298,7 +299,7 @@ impl Token![lint] {
 }
 
 impl Token![lstring] {
-    pub(super) fn inner(&self) -> &str {
+    pub fn inner(&self) -> &str {
         match &self.0.kind {
             TokenKind::String(value) => &value.0,
             _ => unreachable!(),
@@ -309,7 +310,7 @@ impl Token![lstring] {
 // * ------------------------------------------------------------------------------- Statements ------------------------------------------------------------------------------- * //
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) enum Statement {
+pub enum Statement {
     GlobalDecl(
         Token![global],
         Token![ident],
@@ -329,27 +330,27 @@ pub(super) enum Statement {
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct FnDecl {
+pub struct FnDecl {
     _fn: Token![fn],
-    pub(super) name: Token![ident],
-    pub(super) block: FnBlock,
+    pub name: Token![ident],
+    pub block: FnBlock,
 }
 
-pub(super) type EffectTag = Token![ident];
+pub type EffectTag = Token![ident];
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct FnBlock {
-    pub(super) signature: FnSignature,
-    pub(super) effects: SepSeq<EffectTag, Token![,]>,
-    pub(super) expression: Expression,
+pub struct FnBlock {
+    pub signature: FnSignature,
+    pub effects: SepSeq<EffectTag, Token![,]>,
+    pub expression: Expression,
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct FnSignature {
+pub struct FnSignature {
     lpr: Token![lpr],
-    pub(super) args: SepSeq<ArgDef, Token![,]>,
+    pub args: SepSeq<ArgDef, Token![,]>,
     rpr: Token![rpr],
 }
 
@@ -364,49 +365,49 @@ impl Spanned for FnBlock {
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct ArgDef {
-    pub(super) name: Token![ident],
+pub struct ArgDef {
+    pub name: Token![ident],
     _colon: Token![:],
-    pub(super) arg_type: Token![type],
+    pub arg_type: Token![type],
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct EffectDecl {
+pub struct EffectDecl {
     _effect: Token![effect],
-    pub(super) name: Token![ident],
+    pub name: Token![ident],
     _lbk: Token![lbk],
-    pub(super) handlers: Option<NonEmptySeq<EffectHandlerDecl>>,
+    pub handlers: Option<NonEmptySeq<EffectHandlerDecl>>,
     _rbk: Token![rbk],
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct EffectHandlerDecl {
+pub struct EffectHandlerDecl {
     _ctl: Token![ctl],
-    pub(super) name: Token![ident],
-    pub(super) signature: FnSignature,
+    pub name: Token![ident],
+    pub signature: FnSignature,
     _semi: Token![;],
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct IfStatement {
+pub struct IfStatement {
     _if: Token![if],
-    pub(super) condition: Expression,
-    pub(super) statement: Statement,
-    pub(super) else_statement: Option<ElseStatement>,
+    pub condition: Expression,
+    pub statement: Statement,
+    pub else_statement: Option<ElseStatement>,
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct ElseStatement {
+pub struct ElseStatement {
     _else: Token![else],
-    pub(super) statement: Statement,
+    pub statement: Statement,
 }
 
 #[derive(Clone, Debug)]
-pub(super) enum ExpressionStatement {
+pub enum ExpressionStatement {
     Expression(Box<Expression>, Option<Token![;]>),
     Assign(Box<Assign>),
 }
@@ -452,50 +453,50 @@ where
 }
 
 #[derive(Clone, Debug)]
-pub(super) struct Assign {
-    pub(super) lval: Expression,
+pub struct Assign {
+    pub lval: Expression,
     _assign: Token![=],
-    pub(super) rval: Expression,
+    pub rval: Expression,
     _semi: Token![;],
 }
 
 // * ------------------------------------------------------------------------------- Expressions ------------------------------------------------------------------------------ * //
-pub(super) type Expression = NonEmptySepList<AndExpression, Token![||]>;
-pub(super) type AndExpression = NonEmptySepList<EqExpression, Token![&&]>;
-pub(super) type EqExpression = NonEmptySepList<RelExpression, EqOps>;
+pub type Expression = NonEmptySepList<AndExpression, Token![||]>;
+pub type AndExpression = NonEmptySepList<EqExpression, Token![&&]>;
+pub type EqExpression = NonEmptySepList<RelExpression, EqOps>;
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) enum EqOps {
+pub enum EqOps {
     Eq(Token![==]),
     Ne(Token![!=]),
 }
 
-pub(super) type RelExpression = NonEmptySepList<AddExpression, RelOps>;
+pub type RelExpression = NonEmptySepList<AddExpression, RelOps>;
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) enum RelOps {
+pub enum RelOps {
     Lt(Token![<]),
     Gt(Token![>]),
     Le(Token![<=]),
     Ge(Token![>=]),
 }
 
-pub(super) type AddExpression = NonEmptySepList<MulExpression, AddOps>;
+pub type AddExpression = NonEmptySepList<MulExpression, AddOps>;
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) enum AddOps {
+pub enum AddOps {
     Add(Token![+]),
     Sub(Token![-]),
 }
 
-pub(super) type MulExpression = NonEmptySepList<UnaryExpression, MulOps>;
+pub type MulExpression = NonEmptySepList<UnaryExpression, MulOps>;
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) enum MulOps {
+pub enum MulOps {
     Mul(Token![*]),
     Div(Token![/]),
     Mod(Token![%]),
@@ -503,14 +504,14 @@ pub(super) enum MulOps {
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) enum UnaryExpression {
+pub enum UnaryExpression {
     Unary(UnaryOps, Box<Self>),
     Primary(Box<PrimaryExpression>),
 }
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) enum UnaryOps {
+pub enum UnaryOps {
     Pos(Token![+]),
     Neg(Token![-]),
     Not(Token![!]),
@@ -518,7 +519,7 @@ pub(super) enum UnaryOps {
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) enum PrimaryExpression {
+pub enum PrimaryExpression {
     Parens(ParenExpression),
     Block(BlockExpression),
     FuncCall(FunctionCall),
@@ -536,18 +537,18 @@ pub(super) enum PrimaryExpression {
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) struct ParenExpression {
+pub struct ParenExpression {
     _lpr: Token![lpr],
-    pub(super) exp: Expression,
+    pub exp: Expression,
     _rpr: Token![rpr],
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct BlockExpression {
+pub struct BlockExpression {
     lbk: Token![lbk],
-    pub(super) with_handlers: Option<NonEmptySeq<WithHandlers>>,
-    pub(super) statements: Option<NonEmptySeq<Statement>>,
+    pub with_handlers: Option<NonEmptySeq<WithHandlers>>,
+    pub statements: Option<NonEmptySeq<Statement>>,
     rbk: Token![rbk],
 }
 
@@ -559,41 +560,37 @@ impl Spanned for BlockExpression {
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct WithHandlers {
+pub struct WithHandlers {
     _with: Token![with],
-    pub(super) effect: Token![ident],
+    pub effect: Token![ident],
     _lbk: Token![lbk],
-    pub(super) handlers: NonEmptySeq<EffectHandler>,
+    pub handlers: NonEmptySeq<EffectHandler>,
     _rbk: Token![rbk],
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct EffectHandler(
-    pub(super) Token![ctl],
-    pub(super) Token![ident],
-    pub(super) FnBlock,
-);
+pub struct EffectHandler(pub Token![ctl], pub Token![ident], pub FnBlock);
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) struct Array {
+pub struct Array {
     _lbc: Token![lbc],
-    pub(super) values: SepSeq<Expression, Token![,]>,
+    pub values: SepSeq<Expression, Token![,]>,
     _rbc: Token![rbc],
 }
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) struct Table {
+pub struct Table {
     _lbk: Token![lbk],
-    pub(super) values: Option<NonEmptySeq<TableEntry>>,
+    pub values: Option<NonEmptySeq<TableEntry>>,
     _rbk: Token![rbk],
 }
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) enum TableEntry {
+pub enum TableEntry {
     Property(Token![ident], Token![=], Expression, Token![;]),
     Indexed(Expression, Token![=], Expression, Token![;]),
 }
@@ -601,18 +598,18 @@ pub(super) enum TableEntry {
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
 #[starts_with(Token![ident], Token![lpr])]
-pub(super) struct FunctionCall {
-    pub(super) name: Token![ident],
+pub struct FunctionCall {
+    pub name: Token![ident],
     _lpr: Token![lpr],
-    pub(super) args: SepSeq<Expression, Token![,]>,
+    pub args: SepSeq<Expression, Token![,]>,
     _rpr: Token![rpr],
 }
 
 #[derive(Parse, Clone, Debug)]
 #[token(Token)]
-pub(super) struct Access {
-    pub(super) ident: Token![ident],
-    pub(super) index: Option<Index>,
+pub struct Access {
+    pub ident: Token![ident],
+    pub index: Option<Index>,
 }
 
 impl Spanned for Access {
@@ -626,15 +623,16 @@ impl Spanned for Access {
 
 #[derive(Parse, Clone, Spanned, Debug)]
 #[token(Token)]
-pub(super) struct Index {
+pub struct Index {
     _lbc: Token![lbc],
-    pub(super) index: Expression,
+    pub index: Expression,
     _rbc: Token![rbc],
 }
 
 // * --------------------------------------------------------------------------------- Program -------------------------------------------------------------------------------- * //
+/// A parsed source file, ready to be evaluated any number of times.
 pub struct Program {
-    pub(super) statements: Vec<Statement>,
+    pub statements: Vec<Statement>,
 }
 
 impl Program {
@@ -0,0 +1,720 @@
+//! Shell style globs and a small backtracking regex engine.
+//!
+//! Regexes support literals, `.`, classes like `[a-z]` and `[^0-9]`, `\d`, `\w`, `\s`
+//! (and their negations), anchors `^` and `$`, groups with `|` and the quantifiers
+//! `*`, `+`, `?` and `{n,m}`, each of them lazy with a trailing `?`.
+use std::ops::Range;
+
+/// `*` matches any text, `?` any character, `[abc]`/`[!a-z]` a class and `\` escapes.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let tokens = glob_tokens(&pattern.chars().collect::<Vec<_>>());
+    let text = text.chars().collect::<Vec<_>>();
+    // * Only the last `*` has to be backtracked to, matching less with an earlier one
+    // * can't help the tokens after the last `*` match
+    let (mut token, mut index) = (0, 0);
+    let mut star = None;
+    while index < text.len() {
+        match tokens.get(token) {
+            Some(GlobToken::Star) => {
+                star = Some((token, index));
+                token += 1;
+                continue;
+            }
+            Some(glob) if glob.matches(text[index]) => {
+                token += 1;
+                index += 1;
+                continue;
+            }
+            _ => (),
+        }
+        let Some((star_token, star_index)) = star else {
+            return false;
+        };
+        star = Some((star_token, star_index + 1));
+        token = star_token + 1;
+        index = star_index + 1;
+    }
+    tokens[token..].iter().all(|glob| *glob == GlobToken::Star)
+}
+
+#[derive(PartialEq)]
+enum GlobToken {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Star,
+}
+
+impl GlobToken {
+    fn matches(&self, char: char) -> bool {
+        match self {
+            GlobToken::Char(expected) => char == *expected,
+            GlobToken::Any => true,
+            GlobToken::Class { ranges, negated } => class_contains(ranges, char) != *negated,
+            GlobToken::Star => unreachable!("Stars are matched by glob_match"),
+        }
+    }
+}
+
+/// Splits a glob into tokens, a run of `*` is the same as one.
+fn glob_tokens(pattern: &[char]) -> Vec<GlobToken> {
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < pattern.len() {
+        let token = match pattern[index] {
+            '*' if tokens.last() == Some(&GlobToken::Star) => {
+                index += 1;
+                continue;
+            }
+            '*' => GlobToken::Star,
+            '?' => GlobToken::Any,
+            '[' => {
+                let rest = &pattern[index + 1..];
+                let negated = matches!(rest.first(), Some('!' | '^'));
+                let class = if negated { &rest[1..] } else { rest };
+                // * A `]` right at the start is a part of the class
+                match class.iter().skip(1).position(|char| *char == ']') {
+                    Some(end) => {
+                        index += 1 + negated as usize + end + 1;
+                        GlobToken::Class {
+                            ranges: class_ranges(&class[..end + 1]),
+                            negated,
+                        }
+                    }
+                    None => GlobToken::Char('['),
+                }
+            }
+            '\\' if index + 1 < pattern.len() => {
+                index += 1;
+                GlobToken::Char(pattern[index])
+            }
+            char => GlobToken::Char(char),
+        };
+        tokens.push(token);
+        index += 1;
+    }
+    tokens
+}
+
+fn class_ranges(class: &[char]) -> Vec<(char, char)> {
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index < class.len() {
+        if index + 2 < class.len() && class[index + 1] == '-' {
+            ranges.push((class[index], class[index + 2]));
+            index += 3;
+        } else {
+            ranges.push((class[index], class[index]));
+            index += 1;
+        }
+    }
+    ranges
+}
+
+fn class_contains(ranges: &[(char, char)], char: char) -> bool {
+    ranges
+        .iter()
+        .any(|(start, end)| (*start..=*end).contains(&char))
+}
+
+// * ------------------------------------- Regex ------------------------------------ * //
+/// Repetitions are unrolled when compiling, this keeps `(a{100}){100}` from growing forever.
+const MAX_INSTRUCTIONS: usize = 2000;
+/// Groups are parsed recursively.
+const MAX_NESTING: usize = 100;
+
+pub struct Regex {
+    program: Vec<Instruction>,
+    groups: usize,
+}
+
+/// Char ranges of the whole match and of every group, `None` for groups that didn't take part.
+pub type Captures = Vec<Option<Range<usize>>>;
+
+enum Node {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Group(Box<Node>, usize),
+    Alternation(Vec<Node>),
+    Sequence(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+/// Compiled regexes run on a backtracking machine, which keeps its own stack
+/// instead of recursing for every matched character.
+enum Instruction {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    /// Remembers the position in a capture slot, two per group.
+    Save(usize),
+    /// Goes on at the first target, then at the second one if that doesn't match.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            index: 0,
+            groups: 0,
+            depth: 0,
+        };
+        let node = parser.alternation()?;
+        if parser.index < parser.chars.len() {
+            return Err(format!("Unmatched ')' at {}", parser.index));
+        }
+        let mut program = Vec::new();
+        compile(&node, &mut program)?;
+        program.push(Instruction::Match);
+        Ok(Self {
+            program,
+            groups: parser.groups,
+        })
+    }
+
+    /// Whether the regex matches anywhere in the text, use `^` and `$` to match all of it.
+    pub fn is_match(&self, text: &[char]) -> bool {
+        self.find_at(text, 0).is_some()
+    }
+
+    /// Finds the first match starting at or after `start`.
+    pub fn find_at(&self, text: &[char], start: usize) -> Option<Captures> {
+        self.search(
+            text,
+            start,
+            &mut Visited::new(self.program.len(), text.len()),
+        )
+    }
+
+    /// All non-overlapping matches from left to right.
+    pub fn find_all(&self, text: &[char]) -> Vec<Captures> {
+        let mut visited = Visited::new(self.program.len(), text.len());
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while let Some(captures) = self.search(text, start, &mut visited) {
+            let range = captures[0].clone().unwrap_or_default();
+            // * Empty matches have to move on, or the same match is found forever
+            start = if range.is_empty() {
+                range.end + 1
+            } else {
+                range.end
+            };
+            matches.push(captures);
+            if start > text.len() {
+                break;
+            }
+            // * The match went through states at its end, the next search can start from them
+            visited.forget(start);
+        }
+        matches
+    }
+
+    /// States that failed from an earlier position fail from a later one just the same,
+    /// so `visited` is shared by all of them and every state is tried at most once.
+    fn search(&self, text: &[char], start: usize, visited: &mut Visited) -> Option<Captures> {
+        (start..=text.len()).find_map(|position| self.run(text, position, visited))
+    }
+
+    fn run(&self, text: &[char], position: usize, visited: &mut Visited) -> Option<Captures> {
+        enum Job {
+            Try(usize, usize),
+            Restore(usize, Option<usize>),
+        }
+
+        let mut slots = vec![None; (self.groups + 1) * 2];
+        let mut jobs = vec![Job::Try(0, position)];
+        while let Some(job) = jobs.pop() {
+            let (mut pc, mut index) = match job {
+                Job::Try(pc, index) => (pc, index),
+                Job::Restore(slot, value) => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+            // * A state that was already visited either failed or is being tried
+            // * right now, like an empty iteration of a repetition
+            while visited.visit(pc, index) {
+                match &self.program[pc] {
+                    Instruction::Char(char) if text.get(index) == Some(char) => index += 1,
+                    Instruction::Any if index < text.len() && text[index] != '\n' => index += 1,
+                    Instruction::Class { ranges, negated }
+                        if text
+                            .get(index)
+                            .is_some_and(|char| class_contains(ranges, *char) != *negated) =>
+                    {
+                        index += 1
+                    }
+                    Instruction::Start if index == 0 => (),
+                    Instruction::End if index == text.len() => (),
+                    Instruction::Save(slot) => {
+                        jobs.push(Job::Restore(*slot, slots[*slot].replace(index)));
+                    }
+                    Instruction::Split(first, second) => {
+                        jobs.push(Job::Try(*second, index));
+                        pc = *first;
+                        continue;
+                    }
+                    Instruction::Jump(target) => {
+                        pc = *target;
+                        continue;
+                    }
+                    Instruction::Match => {
+                        slots[0] = Some(position);
+                        slots[1] = Some(index);
+                        return Some(
+                            slots
+                                .chunks(2)
+                                .map(|slots| Some(slots[0]?..slots[1]?))
+                                .collect(),
+                        );
+                    }
+                    _ => break,
+                }
+                pc += 1;
+            }
+        }
+        None
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Instruction>) -> Result<(), String> {
+    if program.len() > MAX_INSTRUCTIONS {
+        return Err(String::from("Regex is too long"));
+    }
+    match node {
+        Node::Char(char) => program.push(Instruction::Char(*char)),
+        Node::Any => program.push(Instruction::Any),
+        Node::Class { ranges, negated } => program.push(Instruction::Class {
+            ranges: ranges.clone(),
+            negated: *negated,
+        }),
+        Node::Start => program.push(Instruction::Start),
+        Node::End => program.push(Instruction::End),
+        Node::Group(node, group) => {
+            program.push(Instruction::Save(group * 2));
+            compile(node, program)?;
+            program.push(Instruction::Save(group * 2 + 1));
+        }
+        Node::Alternation(alternatives) => {
+            let mut jumps = Vec::new();
+            for (index, node) in alternatives.iter().enumerate() {
+                if index + 1 == alternatives.len() {
+                    compile(node, program)?;
+                    break;
+                }
+                let split = program.len();
+                program.push(Instruction::Split(split + 1, 0));
+                compile(node, program)?;
+                jumps.push(program.len());
+                program.push(Instruction::Jump(0));
+                program[split] = Instruction::Split(split + 1, program.len());
+            }
+            for jump in jumps {
+                program[jump] = Instruction::Jump(program.len());
+            }
+        }
+        Node::Sequence(nodes) => {
+            for node in nodes {
+                compile(node, program)?;
+            }
+        }
+        Node::Repeat {
+            node,
+            min,
+            max,
+            greedy,
+        } => {
+            let split = |body: usize, exit: usize| {
+                if *greedy {
+                    Instruction::Split(body, exit)
+                } else {
+                    Instruction::Split(exit, body)
+                }
+            };
+            for _ in 0..*min {
+                compile(node, program)?;
+            }
+            match max {
+                Some(max) => {
+                    // * Every optional iteration depends on the one before, `x(x)?` rather than `x?x?`
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Instruction::Jump(0));
+                        compile(node, program)?;
+                    }
+                    for index in splits {
+                        program[index] = split(index + 1, program.len());
+                    }
+                }
+                None => {
+                    let start = program.len();
+                    program.push(Instruction::Jump(0));
+                    compile(node, program)?;
+                    program.push(Instruction::Jump(start));
+                    program[start] = split(start + 1, program.len());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Which instructions were tried at which positions, one bit each.
+struct Visited {
+    bits: Vec<u64>,
+    instructions: usize,
+}
+
+impl Visited {
+    fn new(instructions: usize, len: usize) -> Self {
+        Self {
+            bits: vec![0; (instructions * (len + 1)).div_ceil(64)],
+            instructions,
+        }
+    }
+
+    /// Marks the state as visited, `false` if it already was.
+    fn visit(&mut self, pc: usize, index: usize) -> bool {
+        let bit = index * self.instructions + pc;
+        let (word, mask) = (bit / 64, 1 << (bit % 64));
+        let visited = self.bits[word] & mask != 0;
+        self.bits[word] |= mask;
+        !visited
+    }
+
+    fn forget(&mut self, index: usize) {
+        for bit in index * self.instructions..(index + 1) * self.instructions {
+            self.bits[bit / 64] &= !(1 << (bit % 64));
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    groups: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek();
+        self.index += 1;
+        char
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.index += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Node::Alternation(alternatives)
+        })
+    }
+
+    fn sequence(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while let Some(char) = self.peek() {
+            if char == '|' || char == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantifier(atom)?);
+        }
+        Ok(Node::Sequence(nodes))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let position = self.index;
+        Ok(match self.next() {
+            Some('.') => Node::Any,
+            Some('^') => Node::Start,
+            Some('$') => Node::End,
+            Some('(') => {
+                if self.depth == MAX_NESTING {
+                    return Err(format!("Too many nested groups at {}", position));
+                }
+                self.groups += 1;
+                let group = self.groups;
+                self.depth += 1;
+                let node = self.alternation()?;
+                self.depth -= 1;
+                if self.next() != Some(')') {
+                    return Err(format!("Unclosed '(' at {}", position));
+                }
+                Node::Group(Box::new(node), group)
+            }
+            Some('[') => self.class(position)?,
+            Some('\\') => self.escape(position)?,
+            Some(char @ ('*' | '+' | '?' | '{')) => {
+                return Err(format!("Nothing to repeat with '{}' at {}", char, position))
+            }
+            Some(char) => Node::Char(char),
+            None => unreachable!("Atoms are only parsed before the end"),
+        })
+    }
+
+    fn escape(&mut self, position: usize) -> Result<Node, String> {
+        let class = |ranges: &[(char, char)], negated| Node::Class {
+            ranges: ranges.to_vec(),
+            negated,
+        };
+        const DIGIT: &[(char, char)] = &[('0', '9')];
+        const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+        const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\r')];
+        Ok(match self.next() {
+            Some('d') => class(DIGIT, false),
+            Some('D') => class(DIGIT, true),
+            Some('w') => class(WORD, false),
+            Some('W') => class(WORD, true),
+            Some('s') => class(SPACE, false),
+            Some('S') => class(SPACE, true),
+            Some('n') => Node::Char('\n'),
+            Some('t') => Node::Char('\t'),
+            Some(char) => Node::Char(char),
+            None => return Err(format!("Trailing '\\' at {}", position)),
+        })
+    }
+
+    fn class(&mut self, position: usize) -> Result<Node, String> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.index += 1;
+        }
+        let mut class = Vec::new();
+        loop {
+            match self.next() {
+                // * A `]` right at the start is a part of the class
+                Some(']') if !class.is_empty() => break,
+                Some('\\') => match self.next() {
+                    Some('n') => class.push('\n'),
+                    Some('t') => class.push('\t'),
+                    Some(char) => class.push(char),
+                    None => return Err(format!("Unclosed '[' at {}", position)),
+                },
+                Some(char) => class.push(char),
+                None => return Err(format!("Unclosed '[' at {}", position)),
+            }
+        }
+        Ok(Node::Class {
+            ranges: class_ranges(&class),
+            negated,
+        })
+    }
+
+    fn quantifier(&mut self, node: Node) -> Result<Node, String> {
+        let position = self.index;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                let end = self.chars[self.index..]
+                    .iter()
+                    .position(|char| *char == '}')
+                    .ok_or_else(|| format!("Unclosed '{{' at {}", position))?;
+                let bounds = self.chars[self.index + 1..self.index + end]
+                    .iter()
+                    .collect::<String>();
+                let parse = |bound: &str| {
+                    bound
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid repetition '{{{}}}' at {}", bounds, position))
+                };
+                let (min, max) = match bounds.split_once(',') {
+                    Some((min, max)) if max.trim().is_empty() => (parse(min)?, None),
+                    Some((min, max)) => (parse(min)?, Some(parse(max)?)),
+                    None => (parse(&bounds)?, Some(parse(&bounds)?)),
+                };
+                if max.is_some_and(|max| max < min) {
+                    return Err(format!(
+                        "Invalid repetition '{{{}}}' at {}",
+                        bounds, position
+                    ));
+                }
+                self.index += end;
+                (min, max)
+            }
+            _ => return Ok(node),
+        };
+        self.index += 1;
+        let greedy = self.peek() != Some('?');
+        if !greedy {
+            self.index += 1;
+        }
+        if matches!(node, Node::Start | Node::End) {
+            return Err(format!("Nothing to repeat at {}", position));
+        }
+        Ok(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+            greedy,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Option<String> {
+        let text = text.chars().collect::<Vec<_>>();
+        let captures = Regex::new(pattern).unwrap().find_at(&text, 0)?;
+        Some(text[captures[0].clone()?].iter().collect())
+    }
+
+    fn groups(pattern: &str, text: &str) -> Vec<Option<String>> {
+        let text = text.chars().collect::<Vec<_>>();
+        let captures = Regex::new(pattern).unwrap().find_at(&text, 0).unwrap();
+        let group = |range: Option<Range<usize>>| Some(text[range?].iter().collect());
+        captures.into_iter().map(group).collect()
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(find("^ab", "abab").as_deref(), Some("ab"));
+        assert_eq!(find("^b", "ab"), None);
+        assert_eq!(find("b$", "abab").as_deref(), Some("b"));
+        assert_eq!(find("a$", "ab"), None);
+        assert_eq!(find("^$", "").as_deref(), Some(""));
+        assert_eq!(find("^a|b$", "cab").as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(find("[a-c]+", "xxbcaz").as_deref(), Some("bca"));
+        assert_eq!(find("[^0-9 ]+", "12 ab3").as_deref(), Some("ab"));
+        assert_eq!(find("[]a]+", "x]a]").as_deref(), Some("]a]"));
+        assert_eq!(find("\\d+", "ab 123").as_deref(), Some("123"));
+        assert_eq!(find("\\w+", "  foo_1!").as_deref(), Some("foo_1"));
+        assert_eq!(find("\\s\\S", "a \tb").as_deref(), Some("\tb"));
+        assert_eq!(find("\\D\\W", "1a!").as_deref(), Some("a!"));
+        assert_eq!(find("a.c", "a\nc abc").as_deref(), Some("abc"));
+        assert_eq!(find("\\.", "a.b").as_deref(), Some("."));
+    }
+
+    #[test]
+    fn alternation_and_groups() {
+        assert_eq!(find("cat|dog", "hotdog").as_deref(), Some("dog"));
+        assert_eq!(find("a(b|bc)d", "abcd").as_deref(), Some("abcd"));
+        assert_eq!(find("(|a)b", "ab").as_deref(), Some("ab"));
+        let captures = groups("(\\w+)@(\\w+)(!)?", "mail root@host.");
+        let expected = [Some("root@host"), Some("root"), Some("host"), None];
+        assert_eq!(captures, expected.map(|group| group.map(String::from)));
+        // * A repeated group keeps its last iteration
+        assert_eq!(groups("(a|b)+", "abba")[1].as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn quantifiers() {
+        assert_eq!(find("ab*", "abbbc").as_deref(), Some("abbb"));
+        assert_eq!(find("ab+", "ac"), None);
+        assert_eq!(find("colou?r", "color").as_deref(), Some("color"));
+        assert_eq!(find("a{2,3}", "aaaa").as_deref(), Some("aaa"));
+        assert_eq!(find("a{2}", "aaaa").as_deref(), Some("aa"));
+        assert_eq!(find("a{2,}", "aaaa").as_deref(), Some("aaaa"));
+        assert_eq!(find("^a{2,3}$", "a"), None);
+        assert_eq!(find("<.+?>", "<a><b>").as_deref(), Some("<a>"));
+        assert_eq!(find("a{1,3}?", "aaa").as_deref(), Some("a"));
+        assert_eq!(find("(a*)*b", "aaab").as_deref(), Some("aaab"));
+        assert_eq!(find("(a?)*$", "aa").as_deref(), Some("aa"));
+    }
+
+    #[test]
+    fn find_all() {
+        let text = "a1b22c333".chars().collect::<Vec<_>>();
+        let matches = Regex::new("\\d+").unwrap().find_all(&text);
+        let ranges = matches.into_iter().map(|captures| captures[0].clone());
+        assert_eq!(
+            ranges.collect::<Vec<_>>(),
+            [Some(1..2), Some(3..5), Some(6..9)]
+        );
+        let text = "aa".chars().collect::<Vec<_>>();
+        let matches = Regex::new("a*").unwrap().find_all(&text);
+        let ranges = matches.into_iter().map(|captures| captures[0].clone());
+        assert_eq!(ranges.collect::<Vec<_>>(), [Some(0..2), Some(2..2)]);
+    }
+
+    #[test]
+    fn invalid_regexes() {
+        for pattern in ["(a", "a)", "[a", "*a", "a{2,1}", "a{x}", "\\", "^*"] {
+            assert!(Regex::new(pattern).is_err(), "{}", pattern);
+        }
+        assert!(Regex::new("(a{100}){100}").is_err());
+        assert!(Regex::new(&"(".repeat(1000)).is_err());
+    }
+
+    #[test]
+    fn long_input() {
+        let text = "a".repeat(200_000).chars().collect::<Vec<_>>();
+        for pattern in [".*", "^(a|b)*$", "a*?$", "(a*)*b", "(a|aa)+c"] {
+            let regex = Regex::new(pattern).unwrap();
+            let expected = !pattern.ends_with('b') && !pattern.ends_with('c');
+            assert_eq!(regex.is_match(&text), expected, "{}", pattern);
+        }
+        let text = "a".repeat(200_000);
+        assert!(glob_match("*a*a*a*", &text));
+        assert!(!glob_match("*a*a*b*", &text));
+        assert!(glob_match("a?*", &text));
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("*.gc", "ls.gc"));
+        assert!(!glob_match("*.gc", "ls.gcx"));
+        assert!(glob_match("l?.gc", "ls.gc"));
+        assert!(glob_match("[a-m]s*", "ls.gc"));
+        assert!(!glob_match("[!a-m]s*", "ls.gc"));
+        assert!(glob_match("[^a-m]s*", "ss"));
+        assert!(glob_match("[]]", "]"));
+        assert!(glob_match("a[", "a["));
+        assert!(glob_match("\\*", "*"));
+        assert!(!glob_match("\\*", "a"));
+        assert!(glob_match("", ""));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn double_star_globs() {
+        assert!(glob_match("**", ""));
+        assert!(glob_match("**", "any/thing"));
+        assert!(glob_match("a**b", "ab"));
+        assert!(glob_match("a**b", "a/x/b"));
+        assert!(!glob_match("a**b", "a/x/c"));
+        assert!(glob_match("**.gc", "bin/ls.gc"));
+        assert!(glob_match("*a**b*", "xxaxxbxx"));
+    }
+}
@@ -0,0 +1,342 @@
+//! Saving what should outlive the game: the filesystem and the globals the host picks,
+//! like the shell history. Saves are RON and carry a version, so older ones can be upgraded.
+use super::encoding::{hex_encode, sha256};
+use super::executor::{bail, ensure, Result, Scopes, Value};
+use super::vfs::Vfs;
+use super::{data, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Version of the save format, bumped when [`Save`] changes.
+pub const VERSION: u32 = 1;
+
+/// Where saves are kept.
+pub trait Storage {
+    /// The last save, `None` if nothing was saved yet.
+    fn load(&self) -> Result<Option<String>>;
+    fn save(&mut self, save: &str) -> Result<()>;
+    /// Moves a save that couldn't be restored out of the way, so the next save doesn't overwrite it.
+    fn set_aside(&mut self) -> Result<()>;
+}
+
+/// A save file on disk.
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self) -> Result<Option<String>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let save = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read save '{}'!", self.path.display()))?;
+        Ok(Some(save))
+    }
+
+    fn save(&mut self, save: &str) -> Result<()> {
+        std::fs::write(&self.path, save)
+            .with_context(|| format!("Failed to write save '{}'!", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Renames `save.ron` to `save.ron.broken`, replacing an older broken save.
+    fn set_aside(&mut self) -> Result<()> {
+        let mut name = self.path.file_name().unwrap_or_default().to_owned();
+        name.push(".broken");
+        let broken = self.path.with_file_name(name);
+        std::fs::rename(&self.path, &broken).with_context(|| {
+            format!(
+                "Failed to move save '{}' to '{}'!",
+                self.path.display(),
+                broken.display()
+            )
+        })?;
+        Ok(())
+    }
+}
+
+/// Keeps the save in memory, for tests and platforms without files.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    pub save: Option<String>,
+    /// The save that was set aside last.
+    pub broken: Option<String>,
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self) -> Result<Option<String>> {
+        Ok(self.save.clone())
+    }
+
+    fn save(&mut self, save: &str) -> Result<()> {
+        self.save = Some(save.to_owned());
+        Ok(())
+    }
+
+    fn set_aside(&mut self) -> Result<()> {
+        self.broken = self.save.take();
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Save {
+    version: u32,
+    /// SHA-256 of every built-in file the game had when saving, to tell which ones the player changed.
+    builtin: BTreeMap<String, String>,
+    vfs: Vfs,
+    /// RON table of the saved globals.
+    globals: String,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl Scopes {
+    /// Saves the filesystem and the `globals` that are set, which can only hold data.
+    pub fn save(&self, storage: &mut dyn Storage, globals: &[&str]) -> Result<()> {
+        let mut table = BTreeMap::new();
+        for name in globals {
+            if let Some(value) = self.get_global(name) {
+                table.insert(Value::String(name.to_string()), value.clone());
+            }
+        }
+        let Ok(globals) = data::to_ron(&Value::Table(table), false) else {
+            bail!("Only data can be saved! Globals: {}", globals.join(", "));
+        };
+        let save = Save {
+            version: VERSION,
+            builtin: fingerprint(&Vfs::builtin()),
+            vfs: self.vfs().clone(),
+            globals,
+        };
+        storage.save(&ron::to_string(&save).context("Failed to encode the save!")?)
+    }
+
+    /// Restores the last save, returns `false` if there is none.
+    /// Built-in files the player didn't change are updated to this version of the game.
+    pub fn restore(&mut self, storage: &dyn Storage) -> Result<bool> {
+        let Some(text) = storage.load()? else {
+            return Ok(false);
+        };
+        let version = ron::from_str::<Version>(&text)
+            .context("The save is corrupted!")?
+            .version;
+        ensure!(
+            version <= VERSION,
+            "The save is from a newer version of the game! Version: {}",
+            version
+        );
+        // * Versions start at 1, saves older than the current one would be upgraded here
+        ensure!(
+            version == VERSION,
+            "The save has an unknown version! Version: {}",
+            version
+        );
+        let save = ron::from_str::<Save>(&text).context("The save is corrupted!")?;
+
+        let mut vfs = save.vfs;
+        upgrade_builtin(&mut vfs, &save.builtin)?;
+        *self.vfs_mut() = vfs;
+        if let Value::Table(globals) = data::from_ron(&save.globals)? {
+            for (name, value) in globals {
+                if let Value::String(name) = name {
+                    self.set_global(&name, value);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn fingerprint(vfs: &Vfs) -> BTreeMap<String, String> {
+    vfs.files()
+        .into_iter()
+        .filter_map(|path| {
+            let hash = hex_encode(&sha256(vfs.file(&path)?.as_bytes()));
+            Some((path, hash))
+        })
+        .collect()
+}
+
+/// Replaces built-in files that changed since the save unless the player changed them too,
+/// and adds new ones the player has no file in place of.
+fn upgrade_builtin(vfs: &mut Vfs, saved: &BTreeMap<String, String>) -> Result<()> {
+    let mut builtin = Vfs::builtin();
+    builtin.set_user("root", vec![String::from("root")]);
+    vfs.as_root(|vfs| {
+        for (path, hash) in fingerprint(&builtin) {
+            let current = vfs
+                .file(&path)
+                .map(|content| hex_encode(&sha256(content.as_bytes())));
+            let outdated = match (saved.get(&path), current) {
+                (Some(saved), Some(current)) => *saved != hash && current == *saved,
+                (None, None) => !vfs.exists(&path),
+                _ => false,
+            };
+            if outdated {
+                vfs.copy(&builtin, &path)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes() -> Scopes {
+        let mut scopes = Scopes::default();
+        *scopes.vfs_mut() = Vfs::builtin();
+        scopes
+    }
+
+    fn error<T>(result: Result<T>) -> String {
+        match result {
+            Err(error) => error.message().unwrap_or_default(),
+            std::result::Result::Ok(_) => panic!("Expected an error"),
+        }
+    }
+
+    fn hash(content: &str) -> String {
+        hex_encode(&sha256(content.as_bytes()))
+    }
+
+    #[test]
+    fn save_and_restore() {
+        let mut scopes = scopes();
+        let vfs = scopes.vfs_mut();
+        vfs.write("/home/notes", String::from("remember")).unwrap();
+        vfs.chmod("/home/notes", 0o600).unwrap();
+        vfs.mkdir("/home/projects").unwrap();
+        let history = Value::Array(vec![Value::String(String::from("ls"))]);
+        scopes.set_global("history", history.clone());
+        scopes.set_global("unsaved", Value::Int(1));
+        let mut storage = MemoryStorage::default();
+        scopes.save(&mut storage, &["history", "missing"]).unwrap();
+
+        let mut restored = Scopes::default();
+        assert!(restored.restore(&storage).unwrap());
+        let vfs = restored.vfs();
+        assert_eq!(vfs.file("/home/notes"), Some("remember"));
+        assert_eq!(vfs.stat("/home/notes").unwrap().metadata.mode, 0o600);
+        assert!(vfs.exists("/home/projects"));
+        assert_eq!(vfs.user(), scopes.vfs().user());
+        assert_eq!(vfs.working_directory(), scopes.vfs().working_directory());
+        assert_eq!(restored.get_global("history"), Some(&history));
+        assert_eq!(restored.get_global("unsaved"), None);
+        assert_eq!(restored.get_global("missing"), None);
+    }
+
+    #[test]
+    fn nothing_to_restore() {
+        assert!(!scopes().restore(&MemoryStorage::default()).unwrap());
+    }
+
+    #[test]
+    fn only_data_is_saved() {
+        let mut scopes = scopes();
+        scopes.set_global("table", Value::Table(BTreeMap::new()));
+        scopes.set_global("never", Value::Never);
+        let error = error(scopes.save(&mut MemoryStorage::default(), &["table", "never"]));
+        assert_eq!(error, "Only data can be saved! Globals: table, never");
+    }
+
+    #[test]
+    fn versions() {
+        let mut storage = MemoryStorage::default();
+        scopes().save(&mut storage, &[]).unwrap();
+        let save = storage.save.clone().unwrap();
+        let current = format!("(version:{},", VERSION);
+        assert!(save.starts_with(&current));
+        for (version, message) in [
+            (0, "The save has an unknown version!"),
+            (VERSION + 1, "The save is from a newer version of the game!"),
+        ] {
+            let save = save.replacen(&current, &format!("(version:{},", version), 1);
+            let storage = MemoryStorage {
+                save: Some(save),
+                ..Default::default()
+            };
+            let message = format!("{} Version: {}", message, version);
+            assert_eq!(error(scopes().restore(&storage)), message);
+        }
+        for save in ["", "(vfs: ())", "(version: 1)"] {
+            let storage = MemoryStorage {
+                save: Some(String::from(save)),
+                ..Default::default()
+            };
+            assert_eq!(error(scopes().restore(&storage)), "The save is corrupted!");
+        }
+    }
+
+    #[test]
+    fn upgrade_builtin_files() {
+        let builtin = Vfs::builtin();
+        let mut saved = fingerprint(&builtin);
+        let mut vfs = builtin.clone();
+        vfs.as_root(|vfs| {
+            // * Unchanged since an older version of the game, gets upgraded
+            vfs.write("/bin/ls", String::from("old ls"))?;
+            // * Changed by the player since, stays
+            vfs.write("/bin/grep", String::from("my grep"))?;
+            // * Removed by the player, stays removed
+            vfs.remove("/bin/sl", false)?;
+            // * New in this version, gets added
+            vfs.remove("/bin/pwd", false)?;
+            // * New in this version, but the player has a file there already
+            vfs.write("/bin/whoami", String::from("my whoami"))
+        })
+        .unwrap();
+        saved.insert(String::from("/bin/ls"), hash("old ls"));
+        saved.insert(String::from("/bin/grep"), hash("old grep"));
+        saved.remove("/bin/pwd");
+        saved.remove("/bin/whoami");
+
+        upgrade_builtin(&mut vfs, &saved).unwrap();
+        assert_eq!(vfs.file("/bin/ls"), builtin.file("/bin/ls"));
+        assert_eq!(vfs.file("/bin/grep"), Some("my grep"));
+        assert!(!vfs.exists("/bin/sl"));
+        assert_eq!(vfs.file("/bin/pwd"), builtin.file("/bin/pwd"));
+        assert_eq!(vfs.stat("/bin/pwd").unwrap().metadata.mode, 0o755);
+        assert_eq!(vfs.file("/bin/whoami"), Some("my whoami"));
+        assert_eq!(vfs.user(), builtin.user());
+    }
+
+    #[test]
+    fn broken_saves_are_set_aside() {
+        let directory = std::env::temp_dir().join(format!("gclang-storage-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("save.ron");
+        std::fs::write(&path, "not a save").unwrap();
+
+        let mut storage = FileStorage::new(&path);
+        assert_eq!(error(scopes().restore(&storage)), "The save is corrupted!");
+        storage.set_aside().unwrap();
+        assert_eq!(storage.load().unwrap(), None);
+        scopes().save(&mut storage, &[]).unwrap();
+        let broken = std::fs::read_to_string(directory.join("save.ron.broken")).unwrap();
+        assert_eq!(broken, "not a save");
+        assert!(scopes().restore(&storage).unwrap());
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let mut storage = MemoryStorage {
+            save: Some(String::from("not a save")),
+            ..Default::default()
+        };
+        storage.set_aside().unwrap();
+        assert_eq!(storage.save, None);
+        assert_eq!(storage.broken.as_deref(), Some("not a save"));
+    }
+}
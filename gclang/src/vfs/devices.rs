@@ -0,0 +1,130 @@
+//! Files bound to host state, like `/proc` and `/sys` on Linux. The filesystem only keeps
+//! an empty file for permissions and listings, reads and writes go to a [`crate::Device`]
+//! of the host's [`crate::Library`] instead.
+use super::path::split;
+use super::{Access, Vfs};
+use crate::executor::Result;
+
+#[derive(Clone, Debug)]
+pub(super) struct Binding {
+    device: String,
+    mode: u16,
+}
+
+impl Vfs {
+    /// Binds the file at `path` to a device, creating it with `mode` if it doesn't exist.
+    pub fn bind(&mut self, path: &str, device: &str, mode: u16) -> Result<()> {
+        let path = self.resolve(path);
+        let binding = Binding {
+            device: device.to_owned(),
+            mode,
+        };
+        self.devices.insert(path.clone(), binding);
+        self.create_device_files()
+    }
+
+    /// The device a file is bound to, once the current user may `access` it.
+    /// `None` for other files and ones that don't exist.
+    pub fn device(&self, path: &str, access: Access) -> Result<Option<&str>> {
+        if self.mounted(path).is_some() {
+            return Ok(None);
+        }
+        let Ok((inode, path)) = self.walk(path, true, true) else {
+            return Ok(None);
+        };
+        let Some(binding) = self.devices.get(&path) else {
+            return Ok(None);
+        };
+        self.check(inode, access, &path)?;
+        Ok(Some(&binding.device))
+    }
+
+    pub(super) fn is_device(&self, path: &str) -> bool {
+        self.devices.contains_key(path)
+    }
+
+    /// Makes the files of bound devices that don't exist, e.g. after the tree was replaced.
+    pub(super) fn create_device_files(&mut self) -> Result<()> {
+        let devices = self.devices.clone();
+        self.as_root(|vfs| {
+            for (path, binding) in devices {
+                if vfs.exists(&path) {
+                    continue;
+                }
+                if let Some((parent, _)) = split(&path) {
+                    vfs.mkdir_all(parent)?;
+                }
+                vfs.write(&path, String::new())?;
+                vfs.chmod(&path, binding.mode)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bound() -> Vfs {
+        let mut vfs = Vfs::builtin();
+        vfs.bind("/sys/level/index", "level", 0o444).unwrap();
+        vfs.bind("/proc/player/max_jumps", "jumps", 0o666).unwrap();
+        vfs
+    }
+
+    #[test]
+    fn bind_creates_the_file() {
+        let vfs = bound();
+        let stat = vfs.stat("/sys/level/index").unwrap();
+        assert_eq!((stat.kind, stat.metadata.mode), ("file", 0o444));
+        assert_eq!(stat.metadata.owner, "root");
+        assert_eq!(vfs.file("/proc/player/max_jumps"), Some(""));
+        assert!(vfs.is_device("/sys/level/index"));
+        assert!(!vfs.is_device("/home/log"));
+    }
+
+    #[test]
+    fn device_checks_access() {
+        let mut vfs = bound();
+        let device = vfs.device("/sys/level/index", Access::Read).unwrap();
+        assert_eq!(device, Some("level"));
+        let denied = vfs.device("/sys/level/index", Access::Write);
+        let denied = denied.err().and_then(|error| error.message());
+        assert_eq!(
+            denied.as_deref(),
+            Some("Permission denied! Path: '/sys/level/index'")
+        );
+        let device = vfs.device("/proc/player/max_jumps", Access::Write).unwrap();
+        assert_eq!(device, Some("jumps"));
+        // * Links lead to the device, other files and missing ones aren't devices
+        vfs.symlink("/sys/level/index", "/home/level").unwrap();
+        let device = vfs.device("/home/level", Access::Read).unwrap();
+        assert_eq!(device, Some("level"));
+        assert_eq!(vfs.device("/home/log", Access::Read).unwrap(), None);
+        assert_eq!(vfs.device("/home/missing", Access::Read).unwrap(), None);
+    }
+
+    #[test]
+    fn device_files_survive_resets() {
+        let mut vfs = bound();
+        assert!(vfs
+            .as_root(|vfs| vfs.factory_reset(&Vfs::builtin()))
+            .is_ok());
+        let stat = vfs.stat("/sys/level/index").unwrap();
+        assert_eq!(stat.metadata.mode, 0o444);
+        assert!(vfs.exists("/proc/player/max_jumps"));
+
+        assert!(vfs
+            .as_root(|vfs| {
+                vfs.remove("/proc", true)?;
+                vfs.remove("/sys/level/index", false)?;
+                vfs.restore(&Vfs::builtin(), "/")
+            })
+            .is_ok());
+        assert!(vfs.exists("/sys/level/index"));
+        assert!(vfs.exists("/proc/player/max_jumps"));
+        // * Device files aren't changes
+        assert!(vfs.diff(&Vfs::builtin(), "/").is_empty());
+    }
+}
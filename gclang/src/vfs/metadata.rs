@@ -0,0 +1,70 @@
+//! Who owns a node and who may do what with it, Unix style.
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Mode of new files and directories, `rw-r--r--` and `rwxr-xr-x`.
+pub const FILE_MODE: u16 = 0o644;
+pub const DIRECTORY_MODE: u16 = 0o755;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+impl Access {
+    /// `"r"`, `"w"` or `"x"`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "r" => Some(Self::Read),
+            "w" => Some(Self::Write),
+            "x" => Some(Self::Execute),
+            _ => None,
+        }
+    }
+
+    fn bit(self) -> u16 {
+        match self {
+            Self::Read => 0o4,
+            Self::Write => 0o2,
+            Self::Execute => 0o1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub owner: String,
+    pub group: String,
+    pub mode: u16,
+    /// Time since the Unix epoch, by the clock of the program that changed the node.
+    pub modified: Duration,
+}
+
+impl Metadata {
+    /// Root may do anything, except executing what has no execute bit at all.
+    pub fn allows(&self, user: &str, groups: &[String], access: Access) -> bool {
+        if user == "root" {
+            return access != Access::Execute || self.mode & 0o111 != 0;
+        }
+        let shift = if self.owner == user {
+            6
+        } else if groups.contains(&self.group) {
+            3
+        } else {
+            0
+        };
+        self.mode >> shift & access.bit() != 0
+    }
+
+    /// The mode as `rwxr-xr-x`.
+    pub fn permissions(&self) -> String {
+        (0..9)
+            .map(|index| match self.mode >> (8 - index) & 1 {
+                0 => '-',
+                _ => ['r', 'w', 'x'][index % 3],
+            })
+            .collect()
+    }
+}
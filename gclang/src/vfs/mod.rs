@@ -0,0 +1,758 @@
+//! The in-game filesystem: inodes holding directories and files, addressed by paths
+//! relative to a working directory. Errors are `exception` effects, so scripts can handle
+//! them like any other.
+use super::executor::{bail, Result, Value};
+use metadata::{DIRECTORY_MODE, FILE_MODE};
+use path::{components, split};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+
+mod devices;
+pub mod metadata;
+mod mount;
+mod overlay;
+pub mod path;
+mod snapshot;
+mod users;
+
+pub use metadata::{Access, Metadata};
+pub use mount::Mount;
+pub use overlay::{AppliedOverlay, Overlay, OverlayAction, Policy};
+pub use snapshot::{Change, Snapshot};
+pub use users::Account;
+
+pub type Inode = usize;
+
+const ROOT: Inode = 0;
+/// How many symbolic links a path can go through before it's considered a loop.
+const MAX_LINKS: usize = 40;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NodeKind {
+    File(String),
+    Directory(BTreeMap<String, Inode>),
+    /// A path, relative ones start at the link's directory.
+    Symlink(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub metadata: Metadata,
+    /// Directory entries pointing to the node, it's freed when the last one is removed.
+    pub links: usize,
+}
+
+/// What `fs_stat` reports about a node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stat {
+    pub inode: Inode,
+    pub kind: &'static str,
+    pub size: usize,
+    pub links: usize,
+    pub metadata: Metadata,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Vfs {
+    nodes: HashMap<Inode, Node>,
+    next_inode: Inode,
+    working_directory: String,
+    home_directory: String,
+    user: String,
+    groups: Vec<String>,
+    #[serde(skip)]
+    time: Duration,
+    #[serde(skip)]
+    mounts: Vec<Mount>,
+    #[serde(default)]
+    snapshots: BTreeMap<String, Snapshot>,
+    /// Bound files by path, see [`Vfs::bind`].
+    #[serde(skip)]
+    devices: BTreeMap<String, devices::Binding>,
+}
+
+impl Default for Vfs {
+    fn default() -> Self {
+        Self {
+            nodes: HashMap::from([(
+                ROOT,
+                Node {
+                    kind: NodeKind::Directory(BTreeMap::new()),
+                    metadata: Metadata {
+                        owner: String::from("root"),
+                        group: String::from("root"),
+                        mode: DIRECTORY_MODE,
+                        modified: Duration::ZERO,
+                    },
+                    links: 1,
+                },
+            )]),
+            next_inode: ROOT + 1,
+            working_directory: String::from("/"),
+            home_directory: String::from("/"),
+            user: String::from("root"),
+            groups: vec![String::from("root")],
+            time: Duration::ZERO,
+            mounts: Vec::new(),
+            snapshots: BTreeMap::new(),
+            devices: BTreeMap::new(),
+        }
+    }
+}
+
+impl Vfs {
+    pub fn node(&self, inode: Inode) -> Option<&Node> {
+        self.nodes.get(&inode)
+    }
+
+    /// The absolute, normalized form of `path`.
+    pub fn resolve(&self, path: &str) -> String {
+        path::normalize(&self.working_directory, &self.home_directory, path)
+    }
+
+    pub fn working_directory(&self) -> &str {
+        &self.working_directory
+    }
+
+    pub fn set_working_directory(&mut self, path: &str) -> Result<()> {
+        if let Some((mount, host)) = self.mounted(path) {
+            if mount.stat(&host, path)?.kind != "directory" {
+                bail!(late effect "Not a directory! Path: '{}'", path);
+            }
+            self.working_directory = self.resolve(path);
+            return Ok(());
+        }
+        let inode = self.lookup(path)?;
+        self.directory(inode, path)?;
+        self.check(inode, Access::Execute, path)?;
+        self.working_directory = self.resolve(path);
+        Ok(())
+    }
+
+    pub fn home_directory(&self) -> &str {
+        &self.home_directory
+    }
+
+    pub fn set_home_directory(&mut self, path: &str) {
+        self.home_directory = self.resolve(path);
+    }
+
+    /// The user whose permissions are checked, the first group is the one new files get.
+    pub fn user(&self) -> (&str, &[String]) {
+        (&self.user, &self.groups)
+    }
+
+    pub fn set_user(&mut self, user: &str, groups: Vec<String>) {
+        self.user = user.to_owned();
+        self.groups = groups;
+    }
+
+    /// Runs `f` as root, for changes the game makes rather than the player.
+    pub fn as_root<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let user = std::mem::replace(&mut self.user, String::from("root"));
+        let groups = std::mem::replace(&mut self.groups, vec![String::from("root")]);
+        let result = f(self);
+        self.user = user;
+        self.groups = groups;
+        result
+    }
+
+    /// Sets the time changes are stamped with.
+    pub fn set_time(&mut self, time: Duration) {
+        self.time = time;
+    }
+
+    /// Makes a host directory show up at `mount.path`, which is created if it doesn't exist.
+    /// The root and paths that are mounted already can't be mounted at.
+    pub fn mount(&mut self, mut mount: Mount) -> Result<()> {
+        if !mount.host.is_dir() {
+            bail!(
+                "Can't mount '{}', it's not a directory!",
+                mount.host.display()
+            );
+        }
+        mount.path = self.resolve(&mount.path);
+        if mount.path == "/" {
+            bail!("Can't mount '{}' at the root!", mount.host.display());
+        }
+        if self.mounts.iter().any(|other| other.path == mount.path) {
+            bail!("Something is mounted at '{}' already!", mount.path);
+        }
+        self.as_root(|vfs| vfs.mkdir_all(&mount.path))?;
+        if self.stat(&mount.path)?.kind != "directory" {
+            bail!("Can't mount at '{}', it's not a directory!", mount.path);
+        }
+        self.mounts.push(mount);
+        Ok(())
+    }
+
+    pub fn mounts(&self) -> &[Mount] {
+        &self.mounts
+    }
+
+    /// Finds a node, which takes execute permission on every directory on the way.
+    /// Symbolic links are followed, mounted directories aren't looked into.
+    pub fn lookup(&self, path: &str) -> Result<Inode> {
+        Ok(self.walk(path, true, true)?.0)
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        match self.mounted(path) {
+            Some((_, host)) => host.exists(),
+            None => self.lookup(path).is_ok(),
+        }
+    }
+
+    /// Whether the current user may access the node at `path`.
+    pub fn access(&self, path: &str, access: Access) -> bool {
+        if let Some((mount, host)) = self.mounted(path) {
+            return host.exists()
+                && (mount.writable || access != Access::Write)
+                && mount
+                    .metadata(Duration::ZERO)
+                    .allows(&self.user, &self.groups, access);
+        }
+        self.lookup(path)
+            .is_ok_and(|inode| self.allows(inode, access))
+    }
+
+    pub fn read(&self, path: &str) -> Result<Cow<'_, str>> {
+        if let Some((mount, host)) = self.mounted(path) {
+            return Ok(Cow::Owned(mount.read(&host, path)?));
+        }
+        let inode = self.lookup(path)?;
+        self.check(inode, Access::Read, path)?;
+        match &self.nodes[&inode].kind {
+            NodeKind::File(content) => Ok(Cow::Borrowed(content)),
+            _ => bail!(late effect "Is a directory! Path: '{}'", path),
+        }
+    }
+
+    /// Contents of a file regardless of permissions, for the host to peek at.
+    /// Mounted files aren't included, the host can read them itself.
+    pub fn file(&self, path: &str) -> Option<&str> {
+        match &self.nodes[&self.walk(path, false, true).ok()?.0].kind {
+            NodeKind::File(content) => Some(content),
+            _ => None,
+        }
+    }
+
+    /// Creates the file if it doesn't exist, its directory has to.
+    pub fn write(&mut self, path: &str, content: String) -> Result<()> {
+        if let Some((mount, host)) = self.mounted(path) {
+            return mount.write(&host, path, content);
+        }
+        let path = &self.follow(path)?;
+        let (parent, name) = self.lookup_parent(path)?;
+        match self.directory(parent, path)?.get(&name).copied() {
+            Some(inode) => {
+                self.check(inode, Access::Write, path)?;
+                let node = self.nodes.get_mut(&inode).unwrap();
+                match &mut node.kind {
+                    NodeKind::File(file) => *file = content,
+                    _ => bail!(late effect "Is a directory! Path: '{}'", path),
+                }
+                node.metadata.modified = self.time;
+            }
+            None => {
+                self.check(parent, Access::Write, path)?;
+                self.insert(parent, &name, NodeKind::File(content), FILE_MODE);
+            }
+        }
+        Ok(())
+    }
+
+    /// Names in a directory, sorted.
+    pub fn list(&self, path: &str) -> Result<Vec<String>> {
+        if let Some((mount, host)) = self.mounted(path) {
+            return mount.list(&host, path);
+        }
+        let inode = self.lookup(path)?;
+        let entries = self.directory(inode, path)?;
+        self.check(inode, Access::Read, path)?;
+        Ok(entries.keys().cloned().collect())
+    }
+
+    /// Creates a directory and the ones it's in, unless they exist.
+    pub fn mkdir_all(&mut self, path: &str) -> Result<()> {
+        let path = self.resolve(path);
+        let names = components(&path).collect::<Vec<_>>();
+        for index in 1..=names.len() {
+            let directory = format!("/{}", names[..index].join("/"));
+            if !self.exists(&directory) {
+                self.mkdir(&directory)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn mkdir(&mut self, path: &str) -> Result<()> {
+        if let Some((mount, host)) = self.mounted(path) {
+            return mount.mkdir(&host, path);
+        }
+        let (parent, name) = self.lookup_parent(path)?;
+        if self.directory(parent, path)?.contains_key(&name) {
+            bail!(late effect "File already exists! Path: '{}'", path);
+        }
+        self.check(parent, Access::Write, path)?;
+        let directory = NodeKind::Directory(BTreeMap::new());
+        self.insert(parent, &name, directory, DIRECTORY_MODE);
+        Ok(())
+    }
+
+    /// Makes `path` another name for the file at `existing`.
+    pub fn link(&mut self, existing: &str, path: &str) -> Result<()> {
+        self.unmounted(existing)?;
+        self.unmounted(path)?;
+        let inode = self.lookup(existing)?;
+        if matches!(self.nodes[&inode].kind, NodeKind::Directory(_)) {
+            bail!(late effect "Can't hard link a directory! Path: '{}'", existing);
+        }
+        let (parent, name) = self.lookup_parent(path)?;
+        if self.directory(parent, path)?.contains_key(&name) {
+            bail!(late effect "File already exists! Path: '{}'", path);
+        }
+        self.check(parent, Access::Write, path)?;
+        self.nodes.get_mut(&inode).unwrap().links += 1;
+        let parent = self.nodes.get_mut(&parent).unwrap();
+        if let NodeKind::Directory(entries) = &mut parent.kind {
+            entries.insert(name, inode);
+        }
+        parent.metadata.modified = self.time;
+        Ok(())
+    }
+
+    /// Creates a symbolic link at `path`, `target` doesn't have to exist.
+    pub fn symlink(&mut self, target: &str, path: &str) -> Result<()> {
+        self.unmounted(path)?;
+        let (parent, name) = self.lookup_parent(path)?;
+        if self.directory(parent, path)?.contains_key(&name) {
+            bail!(late effect "File already exists! Path: '{}'", path);
+        }
+        self.check(parent, Access::Write, path)?;
+        let link = NodeKind::Symlink(target.to_owned());
+        self.insert(parent, &name, link, 0o777);
+        Ok(())
+    }
+
+    /// Where a symbolic link points, as it was created.
+    pub fn readlink(&self, path: &str) -> Result<&str> {
+        self.unmounted(path)?;
+        match &self.nodes[&self.walk(path, true, false)?.0].kind {
+            NodeKind::Symlink(target) => Ok(target),
+            _ => bail!(late effect "Not a symbolic link! Path: '{}'", path),
+        }
+    }
+
+    /// Removes a file, or a directory that is empty unless `recursive` is set.
+    pub fn remove(&mut self, path: &str, recursive: bool) -> Result<()> {
+        if let Some((mount, host)) = self.mounted(path) {
+            return mount.remove(&host, path, recursive);
+        }
+        let (parent, name) = self.lookup_parent(path)?;
+        let Some(inode) = self.directory(parent, path)?.get(&name).copied() else {
+            bail!(late effect "File not found! Path: '{}'", path);
+        };
+        if let NodeKind::Directory(entries) = &self.nodes[&inode].kind {
+            if !entries.is_empty() && !recursive {
+                bail!(late effect "Directory isn't empty! Path: '{}'", path);
+            }
+        }
+        self.check(parent, Access::Write, path)?;
+        self.check_removable(inode, path)?;
+        let parent = self.nodes.get_mut(&parent).unwrap();
+        if let NodeKind::Directory(entries) = &mut parent.kind {
+            entries.remove(&name);
+        }
+        parent.metadata.modified = self.time;
+        self.unlink(inode);
+        Ok(())
+    }
+
+    pub fn stat(&self, path: &str) -> Result<Stat> {
+        if let Some((mount, host)) = self.mounted(path) {
+            return mount.stat(&host, path);
+        }
+        Ok(self.stat_of(self.lookup(path)?))
+    }
+
+    /// Like [`Vfs::stat`], but tells about a symbolic link itself.
+    pub fn lstat(&self, path: &str) -> Result<Stat> {
+        if let Some((mount, host)) = self.mounted(path) {
+            return mount.stat(&host, path);
+        }
+        Ok(self.stat_of(self.walk(path, true, false)?.0))
+    }
+
+    /// Only the owner and root can change the mode.
+    pub fn chmod(&mut self, path: &str, mode: u16) -> Result<()> {
+        self.unmounted(path)?;
+        let inode = self.lookup(path)?;
+        let metadata = &mut self.nodes.get_mut(&inode).unwrap().metadata;
+        if self.user != "root" && metadata.owner != self.user {
+            bail!(late effect "Only the owner can change the mode! Path: '{}'", path);
+        }
+        metadata.mode = mode & 0o777;
+        metadata.modified = self.time;
+        Ok(())
+    }
+
+    /// Only root can give nodes away, owners can change the group to one they're in.
+    pub fn chown(&mut self, path: &str, owner: Option<&str>, group: Option<&str>) -> Result<()> {
+        self.unmounted(path)?;
+        let inode = self.lookup(path)?;
+        let metadata = &mut self.nodes.get_mut(&inode).unwrap().metadata;
+        if self.user != "root" {
+            if owner.is_some_and(|owner| owner != self.user) || metadata.owner != self.user {
+                bail!(late effect "Only root can change the owner! Path: '{}'", path);
+            }
+            if group.is_some_and(|group| !self.groups.iter().any(|name| name == group)) {
+                bail!(late effect "You aren't in that group! Path: '{}'", path);
+            }
+        }
+        if let Some(owner) = owner {
+            metadata.owner = owner.to_owned();
+        }
+        if let Some(group) = group {
+            metadata.group = group.to_owned();
+        }
+        metadata.modified = self.time;
+        Ok(())
+    }
+
+    /// Paths of every file, symbolic links aren't followed.
+    pub fn files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        let mut directories = vec![(String::new(), ROOT)];
+        while let Some((directory, inode)) = directories.pop() {
+            if let NodeKind::Directory(entries) = &self.nodes[&inode].kind {
+                for (name, inode) in entries {
+                    let path = format!("{}/{}", directory, name);
+                    match self.nodes[inode].kind {
+                        NodeKind::File(_) => files.push(path),
+                        NodeKind::Directory(_) => directories.push((path, *inode)),
+                        NodeKind::Symlink(_) => (),
+                    }
+                }
+            }
+        }
+        files
+    }
+
+    /// Copies a tree of tables (directories) and strings (files) into the filesystem,
+    /// which is how [`Vfs::builtin`] is defined.
+    pub fn import(&mut self, path: &str, value: &Value) -> Result<()> {
+        match value {
+            Value::String(content) => self.write(path, content.clone()),
+            Value::Table(entries) => {
+                if !self.exists(path) {
+                    self.mkdir(path)?;
+                }
+                for (name, value) in entries {
+                    let Value::String(name) = name else {
+                        bail!("File names have to be strings! Name: {}", name.repr());
+                    };
+                    self.import(&format!("{}/{}", path, name), value)?;
+                }
+                Ok(())
+            }
+            value => bail!(
+                "Only tables and strings can be imported, not {}!",
+                value.type_name()
+            ),
+        }
+    }
+
+    /// Walks a path, following symbolic links on the way and at the end if `follow` is set.
+    /// Returns the node and the path it was reached by without links.
+    fn walk(&self, path: &str, checked: bool, follow: bool) -> Result<(Inode, String)> {
+        let resolved = self.resolve(path);
+        let mut names = components(&resolved)
+            .rev()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut walked = Vec::new();
+        let mut inode = ROOT;
+        let mut links = 0;
+        while let Some(name) = names.pop() {
+            let entries = self.directory(inode, path)?;
+            if checked {
+                self.check(inode, Access::Execute, path)?;
+            }
+            let Some(next) = entries.get(&name).copied() else {
+                bail!(late effect "File not found! Path: '{}'", path);
+            };
+            match &self.nodes[&next].kind {
+                NodeKind::Symlink(target) if follow || !names.is_empty() => {
+                    links += 1;
+                    if links > MAX_LINKS {
+                        bail!(late effect "Too many levels of symbolic links! Path: '{}'", path);
+                    }
+                    let directory = format!("/{}", walked.join("/"));
+                    let target = path::normalize(&directory, &self.home_directory, target);
+                    names.extend(components(&target).rev().map(String::from));
+                    walked.clear();
+                    inode = ROOT;
+                }
+                _ => {
+                    walked.push(name);
+                    inode = next;
+                }
+            }
+        }
+        Ok((inode, format!("/{}", walked.join("/"))))
+    }
+
+    /// Where writing to `path` ends up, links are followed even to files that don't exist yet.
+    fn follow(&self, path: &str) -> Result<String> {
+        let mut resolved = self.resolve(path);
+        for _ in 0..=MAX_LINKS {
+            let Some((parent, name)) = split(&resolved) else {
+                return Ok(resolved);
+            };
+            let (inode, parent) = self.walk(parent, true, true)?;
+            let entry = self.directory(inode, path)?.get(name);
+            resolved = match entry.map(|inode| &self.nodes[inode].kind) {
+                Some(NodeKind::Symlink(target)) => {
+                    path::normalize(&parent, &self.home_directory, target)
+                }
+                _ => return Ok(format!("{}/{}", parent.trim_end_matches('/'), name)),
+            };
+        }
+        bail!(late effect "Too many levels of symbolic links! Path: '{}'", path)
+    }
+
+    /// The mount `path` is in and where it is on the host, later mounts cover earlier ones.
+    fn mounted(&self, path: &str) -> Option<(&Mount, PathBuf)> {
+        let resolved = self.resolve(path);
+        self.mounts
+            .iter()
+            .rev()
+            .find_map(|mount| Some((mount, mount.host_path(&resolved)?)))
+    }
+
+    fn unmounted(&self, path: &str) -> Result<()> {
+        if self.mounted(path).is_some() {
+            bail!(late effect "Host files don't support this! Path: '{}'", path);
+        }
+        Ok(())
+    }
+
+    fn stat_of(&self, inode: Inode) -> Stat {
+        let node = &self.nodes[&inode];
+        let (kind, size) = match &node.kind {
+            NodeKind::File(content) => ("file", content.len()),
+            NodeKind::Directory(entries) => ("directory", entries.len()),
+            NodeKind::Symlink(target) => ("symlink", target.len()),
+        };
+        Stat {
+            inode,
+            kind,
+            size,
+            links: node.links,
+            metadata: node.metadata.clone(),
+        }
+    }
+
+    fn allows(&self, inode: Inode, access: Access) -> bool {
+        self.nodes[&inode]
+            .metadata
+            .allows(&self.user, &self.groups, access)
+    }
+
+    fn check(&self, inode: Inode, access: Access, path: &str) -> Result<()> {
+        if !self.allows(inode, access) {
+            bail!(late effect "Permission denied! Path: '{}'", path);
+        }
+        Ok(())
+    }
+
+    /// Emptying a directory needs write and execute access to it, so the whole subtree is
+    /// checked before anything is removed.
+    fn check_removable(&self, inode: Inode, path: &str) -> Result<()> {
+        let mut directories = vec![(path.trim_end_matches('/').to_owned(), inode)];
+        while let Some((directory, inode)) = directories.pop() {
+            let NodeKind::Directory(entries) = &self.nodes[&inode].kind else {
+                continue;
+            };
+            if entries.is_empty() {
+                continue;
+            }
+            self.check(inode, Access::Write, &directory)?;
+            self.check(inode, Access::Execute, &directory)?;
+            for (name, inode) in entries {
+                directories.push((format!("{}/{}", directory, name), *inode));
+            }
+        }
+        Ok(())
+    }
+
+    fn directory(&self, inode: Inode, path: &str) -> Result<&BTreeMap<String, Inode>> {
+        match &self.nodes[&inode].kind {
+            NodeKind::Directory(entries) => Ok(entries),
+            _ => bail!(late effect "Not a directory! Path: '{}'", path),
+        }
+    }
+
+    /// Splits a path into its parent directory and the last name.
+    fn lookup_parent(&self, path: &str) -> Result<(Inode, String)> {
+        let resolved = self.resolve(path);
+        let Some((parent, name)) = split(&resolved) else {
+            bail!(late effect "Can't change '/'!");
+        };
+        Ok((self.lookup(parent)?, name.to_owned()))
+    }
+
+    fn insert(&mut self, parent: Inode, name: &str, kind: NodeKind, mode: u16) {
+        let inode = self.next_inode;
+        self.next_inode += 1;
+        let metadata = Metadata {
+            owner: self.user.clone(),
+            group: self.groups.first().unwrap_or(&self.user).clone(),
+            mode,
+            modified: self.time,
+        };
+        let node = Node {
+            kind,
+            metadata,
+            links: 1,
+        };
+        self.nodes.insert(inode, node);
+        let parent = self.nodes.get_mut(&parent).unwrap();
+        if let NodeKind::Directory(entries) = &mut parent.kind {
+            entries.insert(name.to_owned(), inode);
+        }
+        parent.metadata.modified = self.time;
+    }
+
+    /// Drops a link to the node, freeing it and what's in it when it was the last.
+    fn unlink(&mut self, inode: Inode) {
+        let node = self.nodes.get_mut(&inode).unwrap();
+        node.links -= 1;
+        if node.links > 0 {
+            return;
+        }
+        if let Some(Node {
+            kind: NodeKind::Directory(entries),
+            ..
+        }) = self.nodes.remove(&inode)
+        {
+            for inode in entries.into_values() {
+                self.unlink(inode);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message<T>(result: Result<T>) -> String {
+        match result {
+            Err(error) => error.message().unwrap_or_default(),
+            std::result::Result::Ok(_) => panic!("Expected an error"),
+        }
+    }
+
+    #[test]
+    fn recursive_remove_needs_access_to_the_whole_tree() {
+        let mut vfs = Vfs::builtin();
+        vfs.mkdir_all("/home/a/b").unwrap();
+        vfs.write("/home/a/b/file", String::from("kept")).unwrap();
+        vfs.write("/home/a/file", String::from("kept")).unwrap();
+        vfs.chmod("/home/a/b", 0o555).unwrap();
+        let error = message(vfs.remove("/home/a", true));
+        assert_eq!(error, "Permission denied! Path: '/home/a/b'");
+        assert_eq!(vfs.file("/home/a/file"), Some("kept"));
+        assert_eq!(vfs.file("/home/a/b/file"), Some("kept"));
+
+        vfs.chmod("/home/a/b", 0o600).unwrap();
+        let error = message(vfs.remove("/home/a/", true));
+        assert_eq!(error, "Permission denied! Path: '/home/a/b'");
+
+        // * An empty directory doesn't need to be writable to be removed
+        vfs.chmod("/home/a/b", 0o700).unwrap();
+        vfs.remove("/home/a/b/file", false).unwrap();
+        vfs.chmod("/home/a/b", 0o500).unwrap();
+        vfs.remove("/home/a", true).unwrap();
+        assert!(!vfs.exists("/home/a"));
+    }
+
+    #[test]
+    fn symbolic_link_loops() {
+        let mut vfs = Vfs::builtin();
+        vfs.symlink("/home/b", "/home/a").unwrap();
+        vfs.symlink("a", "/home/b").unwrap();
+        let error = message(vfs.lookup("/home/a"));
+        assert_eq!(error, "Too many levels of symbolic links! Path: '/home/a'");
+        let error = message(vfs.write("/home/b", String::from("loop")));
+        assert_eq!(error, "Too many levels of symbolic links! Path: '/home/b'");
+        assert_eq!(vfs.readlink("/home/a").unwrap(), "/home/b");
+
+        // * A long chain is fine as long as it stays within the limit
+        vfs.write("/home/target", String::from("end")).unwrap();
+        vfs.symlink("target", "/home/0").unwrap();
+        for index in 1..=MAX_LINKS {
+            vfs.symlink(&(index - 1).to_string(), &format!("/home/{}", index))
+                .unwrap();
+        }
+        assert_eq!(vfs.file(&format!("/home/{}", MAX_LINKS - 1)), Some("end"));
+        assert!(vfs.lookup(&format!("/home/{}", MAX_LINKS)).is_err());
+    }
+
+    #[test]
+    fn relative_symbolic_links() {
+        let mut vfs = Vfs::builtin();
+        vfs.mkdir("/home/dir").unwrap();
+        vfs.write("/home/dir/file", String::from("inside")).unwrap();
+        vfs.write("/home/file", String::from("outside")).unwrap();
+        // * Targets are relative to the directory of the link, not the working directory
+        vfs.symlink("dir/file", "/home/down").unwrap();
+        vfs.symlink("../file", "/home/dir/up").unwrap();
+        vfs.symlink("dir", "/home/shortcut").unwrap();
+        vfs.set_working_directory("/").unwrap();
+        assert_eq!(vfs.file("/home/down"), Some("inside"));
+        assert_eq!(vfs.file("/home/dir/up"), Some("outside"));
+        assert_eq!(vfs.file("/home/shortcut/up"), Some("outside"));
+        assert_eq!(vfs.file("/home/shortcut/file"), Some("inside"));
+        assert_eq!(vfs.readlink("/home/dir/up").unwrap(), "../file");
+    }
+
+    #[test]
+    fn writing_through_a_dangling_link() {
+        let mut vfs = Vfs::builtin();
+        vfs.mkdir("/home/dir").unwrap();
+        vfs.symlink("dir/new", "/home/dangling").unwrap();
+        assert!(vfs.file("/home/dangling").is_none());
+        vfs.write("/home/dangling", String::from("created"))
+            .unwrap();
+        assert_eq!(vfs.file("/home/dir/new"), Some("created"));
+        assert_eq!(vfs.lstat("/home/dangling").unwrap().kind, "symlink");
+
+        vfs.symlink("missing/new", "/home/nowhere").unwrap();
+        let error = message(vfs.write("/home/nowhere", String::from("lost")));
+        assert_eq!(error, "File not found! Path: '/home/missing'");
+    }
+
+    #[test]
+    fn hard_link_counts() {
+        let mut vfs = Vfs::builtin();
+        vfs.write("/home/a", String::from("shared")).unwrap();
+        vfs.link("/home/a", "/home/b").unwrap();
+        let inode = vfs.stat("/home/a").unwrap().inode;
+        assert_eq!(vfs.stat("/home/b").unwrap().inode, inode);
+        assert_eq!(vfs.stat("/home/b").unwrap().links, 2);
+
+        vfs.remove("/home/a", false).unwrap();
+        assert_eq!(vfs.stat("/home/b").unwrap().links, 1);
+        vfs.write("/home/b", String::from("still here")).unwrap();
+        assert_eq!(vfs.file("/home/b"), Some("still here"));
+        vfs.remove("/home/b", false).unwrap();
+        assert!(!vfs.nodes.contains_key(&inode));
+    }
+}
@@ -0,0 +1,242 @@
+//! Host directories mounted into the filesystem, so programs can be written in a real editor.
+//! Host files have no owners: everyone can read and run them, and write them if the mount is
+//! writable. Paths are matched before symbolic links are followed, so links into a mount
+//! don't reach the host.
+use super::path::components;
+use super::{Metadata, Stat};
+use crate::executor::{bail, Result};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mount {
+    /// Where the directory shows up, an absolute path.
+    pub path: String,
+    pub host: PathBuf,
+    pub writable: bool,
+}
+
+impl Mount {
+    /// Takes `--mount DIR` and `--mount-ro DIR` out of command line arguments.
+    /// They mount at `/mnt/host`, `DIR=/path` mounts somewhere else.
+    pub fn from_args(args: &mut Vec<String>) -> Vec<Mount> {
+        let mut mounts = Vec::new();
+        let mut index = 0;
+        while index < args.len() {
+            let writable = match args[index].as_str() {
+                "--mount" => true,
+                "--mount-ro" => false,
+                _ => {
+                    index += 1;
+                    continue;
+                }
+            };
+            args.remove(index);
+            if index < args.len() {
+                let argument = args.remove(index);
+                let (host, path) = argument
+                    .rsplit_once('=')
+                    .unwrap_or((&argument, "/mnt/host"));
+                mounts.push(Mount {
+                    path: path.to_owned(),
+                    host: PathBuf::from(host),
+                    writable,
+                });
+            }
+        }
+        mounts
+    }
+
+    /// The host path for a normalized path, `None` if it's outside the mount.
+    pub fn host_path(&self, path: &str) -> Option<PathBuf> {
+        let rest = path.strip_prefix(self.path.trim_end_matches('/'))?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        Some(components(rest).fold(self.host.clone(), |host, name| host.join(name)))
+    }
+
+    pub(super) fn metadata(&self, modified: Duration) -> Metadata {
+        Metadata {
+            owner: String::from("host"),
+            group: String::from("host"),
+            mode: if self.writable { 0o777 } else { 0o555 },
+            modified,
+        }
+    }
+
+    pub(super) fn read(&self, host: &Path, path: &str) -> Result<String> {
+        if host.is_dir() {
+            bail!(late effect "Is a directory! Path: '{}'", path);
+        }
+        io(std::fs::read_to_string(host), path)
+    }
+
+    pub(super) fn write(&self, host: &Path, path: &str, content: String) -> Result<()> {
+        self.check_writable(path)?;
+        if host.is_dir() {
+            bail!(late effect "Is a directory! Path: '{}'", path);
+        }
+        io(std::fs::write(host, content), path)
+    }
+
+    pub(super) fn list(&self, host: &Path, path: &str) -> Result<Vec<String>> {
+        if host.is_file() {
+            bail!(late effect "Not a directory! Path: '{}'", path);
+        }
+        let mut names = Vec::new();
+        for entry in io(std::fs::read_dir(host), path)? {
+            names.push(io(entry, path)?.file_name().to_string_lossy().into_owned());
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub(super) fn mkdir(&self, host: &Path, path: &str) -> Result<()> {
+        self.check_writable(path)?;
+        if host.exists() {
+            bail!(late effect "File already exists! Path: '{}'", path);
+        }
+        io(std::fs::create_dir(host), path)
+    }
+
+    pub(super) fn remove(&self, host: &Path, path: &str, recursive: bool) -> Result<()> {
+        self.check_writable(path)?;
+        if host == self.host {
+            bail!(late effect "Can't remove a mount point! Path: '{}'", path);
+        }
+        let metadata = io(std::fs::symlink_metadata(host), path)?;
+        if !metadata.is_dir() {
+            io(std::fs::remove_file(host), path)
+        } else if recursive {
+            io(std::fs::remove_dir_all(host), path)
+        } else if io(std::fs::read_dir(host), path)?.next().is_some() {
+            bail!(late effect "Directory isn't empty! Path: '{}'", path)
+        } else {
+            io(std::fs::remove_dir(host), path)
+        }
+    }
+
+    pub(super) fn stat(&self, host: &Path, path: &str) -> Result<Stat> {
+        let metadata = io(std::fs::metadata(host), path)?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let (kind, size) = if metadata.is_dir() {
+            let entries = io(std::fs::read_dir(host), path)?.count();
+            ("directory", entries)
+        } else {
+            ("file", metadata.len() as usize)
+        };
+        Ok(Stat {
+            inode: 0,
+            kind,
+            size,
+            links: 1,
+            metadata: self.metadata(modified),
+        })
+    }
+
+    fn check_writable(&self, path: &str) -> Result<()> {
+        if !self.writable {
+            bail!(late effect "Read-only filesystem! Path: '{}'", path);
+        }
+        Ok(())
+    }
+}
+
+fn io<T>(result: std::io::Result<T>, path: &str) -> Result<T> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(error) => match error.kind() {
+            ErrorKind::NotFound => bail!(late effect "File not found! Path: '{}'", path),
+            ErrorKind::PermissionDenied => {
+                bail!(late effect "Permission denied! Path: '{}'", path)
+            }
+            _ => bail!(late effect "Host error! Path: '{}', {}", path, error),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::Vfs;
+
+    fn host_directory(name: &str) -> PathBuf {
+        let host = std::env::temp_dir().join(format!("gclang-mount-{}", name));
+        std::fs::remove_dir_all(&host).ok();
+        std::fs::create_dir_all(host.join("sub")).unwrap();
+        std::fs::write(host.join("hello.gc"), "println(\"Hello\");").unwrap();
+        host
+    }
+
+    fn mount(path: &str, host: &Path) -> Mount {
+        Mount {
+            path: path.to_owned(),
+            host: host.to_owned(),
+            writable: true,
+        }
+    }
+
+    #[test]
+    fn from_args() {
+        let mut args = [
+            "game",
+            "--mount",
+            "a",
+            "--mount-ro",
+            "b=/x",
+            "level",
+            "--mount",
+        ]
+        .map(String::from)
+        .to_vec();
+        let mounts = Mount::from_args(&mut args);
+        assert_eq!(args, ["game", "level"]);
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(
+            (mounts[0].path.as_str(), mounts[0].writable),
+            ("/mnt/host", true)
+        );
+        assert_eq!((mounts[1].path.as_str(), mounts[1].writable), ("/x", false));
+        assert_eq!(mounts[1].host, PathBuf::from("b"));
+    }
+
+    #[test]
+    fn mount_and_read() {
+        let host = host_directory("read");
+        let mut vfs = Vfs::default();
+        vfs.mount(mount("/mnt/host", &host)).unwrap();
+        assert_eq!(
+            vfs.read("/mnt/host/hello.gc").unwrap(),
+            "println(\"Hello\");"
+        );
+        assert_eq!(vfs.list("/mnt/host").unwrap(), ["hello.gc", "sub"]);
+        vfs.write("/mnt/host/sub/new", String::from("new")).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(host.join("sub/new")).unwrap(),
+            "new"
+        );
+        assert_eq!(vfs.list("/mnt").unwrap(), ["host"]);
+    }
+
+    #[test]
+    fn mount_points() {
+        let host = host_directory("points");
+        let mut vfs = Vfs::default();
+        assert!(vfs.mount(mount("/", &host)).is_err());
+        assert!(vfs.mount(mount("/mnt/..", &host)).is_err());
+        assert!(vfs.mount(mount("/mnt", &host.join("hello.gc"))).is_err());
+        vfs.write("/file", String::new()).unwrap();
+        assert!(vfs.mount(mount("/file", &host)).is_err());
+        assert!(vfs.mounts().is_empty());
+
+        vfs.mount(mount("/mnt/host/", &host)).unwrap();
+        assert!(vfs.mount(mount("/mnt/host", &host.join("sub"))).is_err());
+        assert_eq!(vfs.mounts(), [mount("/mnt/host", &host)]);
+    }
+}
@@ -0,0 +1,226 @@
+//! Files a level puts into the filesystem while its terminal is open.
+//! The game applies them as root, and reverting gives back what was there before.
+//! Overlays only name files, directories they need are created and removed with them.
+use super::path::components;
+use super::{Metadata, Vfs};
+use crate::executor::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Overlay {
+    pub path: String,
+    pub action: OverlayAction,
+    #[serde(default)]
+    pub policy: Policy,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OverlayAction {
+    /// Creates the file unless there already is one.
+    Add(String),
+    /// Creates the file or overwrites it.
+    Replace(String),
+    Remove,
+}
+
+/// What happens to a file when the overlay is reverted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Policy {
+    /// The file goes back to how it was.
+    #[default]
+    Revert,
+    /// The change stays, like a file the player got to keep.
+    Keep,
+}
+
+/// What files looked like before an overlay was applied, `None` if they didn't exist.
+/// Directories that were created for them are `None` too, reverting removes them if empty.
+#[derive(Clone, Debug, Default)]
+pub struct AppliedOverlay {
+    previous: Vec<(String, Option<(String, Metadata)>)>,
+}
+
+impl Vfs {
+    /// Applies the changes in order, undoing them all if one fails.
+    pub fn apply_overlay(&mut self, overlays: &[Overlay]) -> Result<AppliedOverlay> {
+        let mut applied = AppliedOverlay::default();
+        for overlay in overlays {
+            if let Err(error) = self.apply(overlay, &mut applied) {
+                self.revert_overlay(applied).ok();
+                return Err(error);
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Puts back the files an overlay changed, except the ones it keeps.
+    pub fn revert_overlay(&mut self, applied: AppliedOverlay) -> Result<()> {
+        self.as_root(|vfs| {
+            let mut result = Ok(());
+            for (path, previous) in applied.previous.into_iter().rev() {
+                result = result.and(match previous {
+                    Some((content, metadata)) => vfs.restore_file(&path, content, metadata),
+                    None => vfs.remove(&path, false),
+                });
+            }
+            result
+        })
+    }
+
+    fn apply(&mut self, overlay: &Overlay, applied: &mut AppliedOverlay) -> Result<()> {
+        self.as_root(|vfs| {
+            let path = vfs.resolve(&overlay.path);
+            let previous = if vfs.exists(&path) {
+                let stat = vfs.stat(&path)?;
+                if stat.kind == "directory" {
+                    bail!("Overlays can only change files! Path: '{}'", path);
+                }
+                Some((vfs.read(&path)?.into_owned(), stat.metadata))
+            } else {
+                None
+            };
+            let mut changed = Vec::new();
+            match &overlay.action {
+                OverlayAction::Add(_) if previous.is_some() => return Ok(()),
+                OverlayAction::Add(content) | OverlayAction::Replace(content) => {
+                    let names = components(&path).collect::<Vec<_>>();
+                    let mut result = Ok(());
+                    for index in 1..names.len() {
+                        let directory = format!("/{}", names[..index].join("/"));
+                        if result.is_ok() && !vfs.exists(&directory) {
+                            result = vfs.mkdir(&directory);
+                            changed.push((directory, None));
+                        }
+                    }
+                    if let Err(error) = result.and_then(|_| vfs.write(&path, content.clone())) {
+                        for (directory, _) in changed.into_iter().rev() {
+                            vfs.remove(&directory, false).ok();
+                        }
+                        return Err(error);
+                    }
+                }
+                OverlayAction::Remove if previous.is_none() => return Ok(()),
+                OverlayAction::Remove => vfs.remove(&path, false)?,
+            }
+            changed.push((path, previous));
+            if overlay.policy == Policy::Revert {
+                applied.previous.extend(changed);
+            }
+            Ok(())
+        })
+    }
+
+    fn restore_file(&mut self, path: &str, content: String, metadata: Metadata) -> Result<()> {
+        self.write(path, content)?;
+        self.chmod(path, metadata.mode)?;
+        self.chown(path, Some(&metadata.owner), Some(&metadata.group))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlay(path: &str, action: OverlayAction, policy: Policy) -> Overlay {
+        Overlay {
+            path: path.to_owned(),
+            action,
+            policy,
+        }
+    }
+
+    fn vfs() -> Vfs {
+        let mut vfs = Vfs::default();
+        vfs.mkdir("/home").unwrap();
+        vfs.write("/home/log", String::from("old")).unwrap();
+        vfs.chmod("/home/log", 0o600).unwrap();
+        vfs.chown("/home", Some("gc"), Some("gc")).unwrap();
+        vfs.set_user("gc", vec![String::from("gc")]);
+        vfs
+    }
+
+    #[test]
+    fn apply_and_revert() {
+        let mut vfs = vfs();
+        let applied = vfs
+            .apply_overlay(&[
+                overlay(
+                    "/home/log",
+                    OverlayAction::Replace(String::from("new")),
+                    Policy::Revert,
+                ),
+                overlay(
+                    "/home/log",
+                    OverlayAction::Add(String::from("ignored")),
+                    Policy::Revert,
+                ),
+                overlay(
+                    "/etc/motd",
+                    OverlayAction::Add(String::from("hi")),
+                    Policy::Revert,
+                ),
+                overlay("/missing", OverlayAction::Remove, Policy::Revert),
+            ])
+            .unwrap();
+        assert_eq!(vfs.file("/home/log"), Some("new"));
+        assert_eq!(vfs.file("/etc/motd"), Some("hi"));
+        assert_eq!(vfs.user().0, "gc");
+
+        vfs.revert_overlay(applied).unwrap();
+        assert_eq!(vfs.file("/home/log"), Some("old"));
+        assert_eq!(vfs.stat("/home/log").unwrap().metadata.mode, 0o600);
+        assert!(!vfs.exists("/etc"));
+    }
+
+    #[test]
+    fn remove_and_keep() {
+        let mut vfs = vfs();
+        let applied = vfs
+            .apply_overlay(&[
+                overlay("/home/log", OverlayAction::Remove, Policy::Revert),
+                overlay(
+                    "/a/b/key",
+                    OverlayAction::Add(String::from("key")),
+                    Policy::Keep,
+                ),
+            ])
+            .unwrap();
+        assert!(!vfs.exists("/home/log"));
+        vfs.revert_overlay(applied).unwrap();
+        assert_eq!(vfs.file("/home/log"), Some("old"));
+        assert_eq!(vfs.file("/a/b/key"), Some("key"));
+    }
+
+    #[test]
+    fn directories_are_refused() {
+        let mut vfs = vfs();
+        for action in [
+            OverlayAction::Replace(String::new()),
+            OverlayAction::Add(String::new()),
+            OverlayAction::Remove,
+        ] {
+            let overlays = [
+                overlay(
+                    "/new/file",
+                    OverlayAction::Add(String::new()),
+                    Policy::Revert,
+                ),
+                overlay("/home", action, Policy::Revert),
+            ];
+            let error = vfs.apply_overlay(&overlays).err().unwrap();
+            assert_eq!(
+                error.message().unwrap(),
+                "Overlays can only change files! Path: '/home'"
+            );
+            assert!(!vfs.exists("/new"));
+            assert_eq!(vfs.file("/home/log"), Some("old"));
+        }
+        let overlays = [overlay(
+            "/home/log/x",
+            OverlayAction::Add(String::new()),
+            Policy::Revert,
+        )];
+        assert!(vfs.apply_overlay(&overlays).is_err());
+        assert!(vfs.exists("/home/log"));
+    }
+}
@@ -0,0 +1,117 @@
+//! Path resolution: every path the filesystem sees goes through [`normalize`] first.
+
+/// Turns `path` into an absolute path without `.`, `..`, repeated or trailing slashes.
+/// Relative paths start at `working_directory` and a leading `~` is `home_directory`.
+/// `..` at the root stays at the root, like in Unix.
+pub fn normalize(working_directory: &str, home_directory: &str, path: &str) -> String {
+    let (base, path) = match path.strip_prefix('~') {
+        _ if path.starts_with('/') => ("", path),
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => (home_directory, rest),
+        _ => (working_directory, path),
+    };
+
+    let mut components = Vec::new();
+    for name in base.split('/').chain(path.split('/')) {
+        match name {
+            "" | "." => (),
+            ".." => {
+                components.pop();
+            }
+            name => components.push(name),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
+
+/// Splits a normalized path into its parent and last name, `None` for the root.
+pub fn split(path: &str) -> Option<(&str, &str)> {
+    match path.rsplit_once('/')? {
+        (_, "") => None,
+        ("", name) => Some(("/", name)),
+        (parent, name) => Some((parent, name)),
+    }
+}
+
+/// Names along a normalized path, without the root.
+pub fn components(path: &str) -> impl DoubleEndedIterator<Item = &str> {
+    path.split('/').filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const NAMES: [&str; 8] = ["a", "bc", ".", "..", "", "...", ".a", "~"];
+
+    /// Relative, absolute and home paths made of awkward names, the same ones on every run.
+    fn paths() -> impl Iterator<Item = String> {
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..2000).map(move |_| {
+            let names = (0..rng.gen_range(0..8))
+                .map(|_| NAMES[rng.gen_range(0..NAMES.len())])
+                .collect::<Vec<_>>();
+            let prefix = ["", "/", "~/", "~"][rng.gen_range(0..4)];
+            format!("{}{}", prefix, names.join("/"))
+        })
+    }
+
+    fn is_normalized(path: &str) -> bool {
+        path.starts_with('/')
+            && (path == "/" || !path.ends_with('/'))
+            && !path.contains("//")
+            && components(path).all(|name| name != "." && name != "..")
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(normalize("/home/user", "/root", "../x/./y/"), "/home/x/y");
+        assert_eq!(normalize("/home", "/root", "~/docs"), "/root/docs");
+        assert_eq!(normalize("/home", "/root", "~"), "/root");
+        assert_eq!(normalize("/home", "/root", "~user"), "/home/~user");
+        assert_eq!(normalize("/home", "/root", "/a//b/"), "/a/b");
+        assert_eq!(normalize("/home", "/root", ""), "/home");
+    }
+
+    #[test]
+    fn parent_of_root_is_root() {
+        for path in ["/..", "/../..", "/.././..", "..", "../../.."] {
+            assert_eq!(normalize("/", "/", path), "/", "{}", path);
+        }
+        assert_eq!(normalize("/a", "/", "../../b"), "/b");
+    }
+
+    #[test]
+    fn normalize_is_idempotent() {
+        for path in paths() {
+            let normalized = normalize("/home/user", "/root", &path);
+            assert!(is_normalized(&normalized), "{} -> {}", path, normalized);
+            for (working_directory, home_directory) in [("/", "/"), ("/x/y", "/z")] {
+                let again = normalize(working_directory, home_directory, &normalized);
+                assert_eq!(again, normalized, "{}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn split_and_join_round_trip() {
+        assert_eq!(split("/"), None);
+        for path in paths() {
+            let path = normalize("/home/user", "/root", &path);
+            let Some((parent, name)) = split(&path) else {
+                assert_eq!(path, "/");
+                continue;
+            };
+            assert!(is_normalized(parent) && !name.is_empty() && !name.contains('/'));
+            let joined = match parent {
+                "/" => format!("/{}", name),
+                parent => format!("{}/{}", parent, name),
+            };
+            assert_eq!(joined, path);
+            assert_eq!(components(&path).last(), Some(name));
+            let rejoined = format!("/{}", components(&path).collect::<Vec<_>>().join("/"));
+            assert_eq!(rejoined, path);
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! Ways back from a broken filesystem: named snapshots of the whole tree, and comparing
+//! or restoring paths against the original tree, which is [`Vfs::builtin`] in the game.
+use super::path::{components, split};
+use super::{Access, Inode, Node, NodeKind, Vfs, ROOT};
+use crate::executor::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// The tree at some point, without the session (user, working directory and mounts).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    nodes: HashMap<Inode, Node>,
+    next_inode: Inode,
+}
+
+/// How a file differs from the original.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl Change {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Modified => "modified",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+impl Vfs {
+    /// Remembers the tree under `name`, replacing an older snapshot with that name.
+    pub fn snapshot(&mut self, name: &str) {
+        let snapshot = Snapshot {
+            nodes: self.nodes.clone(),
+            next_inode: self.next_inode,
+        };
+        self.snapshots.insert(name.to_owned(), snapshot);
+    }
+
+    /// Puts the tree back to how it was when the snapshot was taken. Only root can.
+    pub fn rollback(&mut self, name: &str) -> Result<()> {
+        let Some(snapshot) = self.snapshots.get(name).cloned() else {
+            bail!(late effect "No such snapshot! Name: '{}'", name);
+        };
+        if self.user != "root" {
+            bail!(late effect "Permission denied! Only root can roll back the filesystem");
+        }
+        self.set_tree(snapshot)
+    }
+
+    pub fn snapshots(&self) -> Vec<&str> {
+        self.snapshots.keys().map(String::as_str).collect()
+    }
+
+    pub fn forget_snapshot(&mut self, name: &str) -> Result<()> {
+        if self.snapshots.remove(name).is_none() {
+            bail!(late effect "No such snapshot! Name: '{}'", name);
+        }
+        Ok(())
+    }
+
+    /// Replaces the whole tree with the original one, snapshots are kept. Only root can.
+    pub fn factory_reset(&mut self, original: &Vfs) -> Result<()> {
+        if self.user != "root" {
+            bail!(late effect "Permission denied! Only root can reset the filesystem");
+        }
+        self.set_tree(Snapshot {
+            nodes: original.nodes.clone(),
+            next_inode: original.next_inode,
+        })
+    }
+
+    /// Files and symbolic links under `path` that aren't like in `original`,
+    /// by contents, owner, group or mode. Directories the user can't list are left out.
+    pub fn diff(&self, original: &Vfs, path: &str) -> BTreeMap<String, Change> {
+        let path = self.resolve(path);
+        let current = self.leaves(&path, &self.user, &self.groups);
+        let original_leaves = original.leaves(&path, &self.user, &self.groups);
+        let mut changes = BTreeMap::new();
+        for (path, inode) in &current {
+            match original_leaves.get(path) {
+                None => {
+                    changes.insert(path.clone(), Change::Added);
+                }
+                Some(original_inode) => {
+                    if !same(&self.nodes[inode], &original.nodes[original_inode]) {
+                        changes.insert(path.clone(), Change::Modified);
+                    }
+                }
+            }
+        }
+        for path in original_leaves.keys() {
+            if !current.contains_key(path) {
+                changes.insert(path.clone(), Change::Removed);
+            }
+        }
+        changes
+    }
+
+    /// Makes everything under `path` like in `original`: changed and removed files come back,
+    /// added ones are removed. Directories that were added are left alone unless `path` is one
+    /// or they are where a file was. The user has to be allowed to change every file and
+    /// remove those directories, or nothing is restored.
+    pub fn restore(&mut self, original: &Vfs, path: &str) -> Result<()> {
+        let path = self.resolve(path);
+        if original.walk(&path, false, false).is_err() {
+            if self.walk(&path, false, false).is_err() {
+                bail!(late effect "File not found! Path: '{}'", path);
+            }
+            self.remove(&path, true)?;
+            return self.create_device_files();
+        }
+        let changes = self.diff(original, &path);
+        for path in changes.keys() {
+            if !self.can_replace(path) {
+                bail!(late effect "Permission denied! Path: '{}'", path);
+            }
+            if let std::result::Result::Ok((inode, _)) = self.walk(path, false, false) {
+                self.check_removable(inode, path)?;
+            }
+        }
+        // * Only root can give files back their owners
+        self.as_root(|vfs| {
+            for (path, change) in changes {
+                // * A removed file can have a directory in its place, and files that
+                // * were added in it are gone with it
+                if vfs.walk(&path, false, false).is_ok() {
+                    vfs.remove(&path, true)?;
+                }
+                if change != Change::Added {
+                    vfs.copy(original, &path)?;
+                }
+            }
+            vfs.create_device_files()
+        })
+    }
+
+    /// Copies a file or symbolic link from `original` with its metadata,
+    /// creating the directories on the way.
+    pub(crate) fn copy(&mut self, original: &Vfs, path: &str) -> Result<()> {
+        let names = components(path).collect::<Vec<_>>();
+        for index in 1..names.len() {
+            let directory = format!("/{}", names[..index].join("/"));
+            if !self.exists(&directory) {
+                self.mkdir(&directory)?;
+                let (inode, _) = original.walk(&directory, false, true)?;
+                let metadata = &original.nodes[&inode].metadata;
+                self.chmod(&directory, metadata.mode)?;
+                self.chown(&directory, Some(&metadata.owner), Some(&metadata.group))?;
+            }
+        }
+        let node = &original.nodes[&original.walk(path, false, false)?.0];
+        match &node.kind {
+            NodeKind::File(content) => self.write(path, content.clone())?,
+            NodeKind::Symlink(target) => return self.symlink(target, path),
+            NodeKind::Directory(_) => bail!(late effect "Is a directory! Path: '{}'", path),
+        }
+        self.chmod(path, node.metadata.mode)?;
+        self.chown(path, Some(&node.metadata.owner), Some(&node.metadata.group))
+    }
+
+    fn set_tree(&mut self, snapshot: Snapshot) -> Result<()> {
+        self.nodes = snapshot.nodes;
+        self.next_inode = snapshot.next_inode;
+        self.create_device_files()?;
+        for directory in [self.working_directory.clone(), self.home_directory.clone()] {
+            if self.walk(&directory, false, true).is_ok() {
+                self.working_directory = directory;
+                return Ok(());
+            }
+        }
+        self.working_directory = String::from("/");
+        Ok(())
+    }
+
+    /// Whether the user can remove or create `path`, which takes writing
+    /// the closest directory above it that exists.
+    fn can_replace(&self, path: &str) -> bool {
+        let mut path = path;
+        while let Some((parent, _)) = split(path) {
+            if self.exists(parent) {
+                return self.access(parent, Access::Write);
+            }
+            path = parent;
+        }
+        false
+    }
+
+    /// Files and symbolic links at or under `path`, without the ones bound to devices
+    /// and the ones in directories `user` can't list.
+    fn leaves(&self, path: &str, user: &str, groups: &[String]) -> BTreeMap<String, Inode> {
+        let mut leaves = BTreeMap::new();
+        let mut directories = vec![(String::new(), ROOT)];
+        while let Some((directory, inode)) = directories.pop() {
+            let metadata = &self.nodes[&inode].metadata;
+            if !metadata.allows(user, groups, Access::Read)
+                || !metadata.allows(user, groups, Access::Execute)
+            {
+                continue;
+            }
+            if let NodeKind::Directory(entries) = &self.nodes[&inode].kind {
+                for (name, inode) in entries {
+                    let entry = format!("{}/{}", directory, name);
+                    match self.nodes[inode].kind {
+                        NodeKind::Directory(_) => directories.push((entry, *inode)),
+                        _ if under(&entry, path) && !self.is_device(&entry) => {
+                            leaves.insert(entry, *inode);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        leaves
+    }
+}
+
+fn under(entry: &str, path: &str) -> bool {
+    match entry.strip_prefix(path.trim_end_matches('/')) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+fn same(node: &Node, original: &Node) -> bool {
+    node.kind == original.kind
+        && node.metadata.owner == original.metadata.owner
+        && node.metadata.group == original.metadata.group
+        && node.metadata.mode == original.metadata.mode
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn as_root(vfs: &mut Vfs, f: impl FnOnce(&mut Vfs) -> Result<()>) {
+        assert!(vfs.as_root(f).is_ok());
+    }
+
+    fn diff(vfs: &Vfs, path: &str) -> Vec<(String, Change)> {
+        vfs.diff(&Vfs::builtin(), path).into_iter().collect()
+    }
+
+    fn message(result: Result<()>) -> String {
+        match result {
+            Err(error) => error.message().unwrap_or_default(),
+            std::result::Result::Ok(()) => panic!("Expected an error"),
+        }
+    }
+
+    #[test]
+    fn snapshot_and_rollback() {
+        let mut vfs = Vfs::builtin();
+        vfs.write("/home/log", String::from("before")).unwrap();
+        vfs.snapshot("start");
+        vfs.write("/home/log", String::from("after")).unwrap();
+        vfs.write("/home/new", String::from("new")).unwrap();
+        vfs.mkdir("/home/dir").unwrap();
+        vfs.set_working_directory("/home/dir").unwrap();
+
+        let denied = message(vfs.rollback("start"));
+        assert_eq!(
+            denied,
+            "Permission denied! Only root can roll back the filesystem"
+        );
+        assert_eq!(vfs.file("/home/log"), Some("after"));
+        as_root(&mut vfs, |vfs| vfs.rollback("start"));
+        assert_eq!(vfs.file("/home/log"), Some("before"));
+        assert!(!vfs.exists("/home/new") && !vfs.exists("/home/dir"));
+        assert_eq!(vfs.working_directory(), "/home");
+        assert_eq!(vfs.snapshots(), ["start"]);
+        vfs.forget_snapshot("start").unwrap();
+        assert!(vfs.rollback("start").is_err());
+        assert!(vfs.forget_snapshot("start").is_err());
+    }
+
+    #[test]
+    fn diff_against_builtin() {
+        let mut vfs = Vfs::builtin();
+        assert!(diff(&vfs, "/").is_empty());
+        vfs.write("/home/log", String::from("changed")).unwrap();
+        vfs.write("/home/new", String::from("new")).unwrap();
+        as_root(&mut vfs, |vfs| {
+            vfs.remove("/bin/sl", false)?;
+            vfs.chmod("/bin/ls", 0o777)
+        });
+        let expected = [
+            (String::from("/bin/ls"), Change::Modified),
+            (String::from("/bin/sl"), Change::Removed),
+            (String::from("/home/log"), Change::Modified),
+            (String::from("/home/new"), Change::Added),
+        ];
+        assert_eq!(diff(&vfs, "/"), expected);
+        assert_eq!(diff(&vfs, "/home"), expected[2..]);
+        assert_eq!(diff(&vfs, "/home/new"), expected[3..]);
+        assert!(diff(&vfs, "/etc").is_empty());
+    }
+
+    #[test]
+    fn diff_hides_what_the_user_cant_list() {
+        let mut vfs = Vfs::builtin();
+        as_root(&mut vfs, |vfs| {
+            vfs.write("/root/secret", String::from("secret"))?;
+            vfs.mkdir("/home/locked")?;
+            vfs.write("/home/locked/file", String::new())?;
+            vfs.chmod("/home/locked", 0o744)
+        });
+        assert!(diff(&vfs, "/").is_empty());
+        assert!(diff(&vfs, "/root").is_empty());
+        as_root(&mut vfs, |vfs| {
+            assert_eq!(diff(vfs, "/").len(), 2);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn restore_paths() {
+        let mut vfs = Vfs::builtin();
+        vfs.snapshot("start");
+        vfs.write("/home/log", String::from("changed")).unwrap();
+        vfs.mkdir("/home/dir").unwrap();
+        vfs.write("/home/dir/file", String::new()).unwrap();
+        vfs.write("/home/new", String::from("new")).unwrap();
+
+        // * Added after the snapshot, so not in the original either
+        vfs.restore(&Vfs::builtin(), "/home/new").unwrap();
+        assert!(!vfs.exists("/home/new"));
+        vfs.restore(&Vfs::builtin(), "/home/dir").unwrap();
+        assert!(!vfs.exists("/home/dir"));
+        vfs.restore(&Vfs::builtin(), "/home/log").unwrap();
+        assert_eq!(vfs.file("/home/log"), Some(""));
+        assert_eq!(vfs.stat("/home/log").unwrap().metadata.owner, "gc");
+        assert!(diff(&vfs, "/").is_empty());
+        assert_eq!(vfs.snapshots(), ["start"]);
+        let message = message(vfs.restore(&Vfs::builtin(), "/home/missing"));
+        assert_eq!(message, "File not found! Path: '/home/missing'");
+    }
+
+    #[test]
+    fn restore_needs_permission() {
+        let mut vfs = Vfs::builtin();
+        as_root(&mut vfs, |vfs| {
+            vfs.remove("/bin/sl", false)?;
+            vfs.write("/root/secret", String::new())
+        });
+        vfs.write("/home/log", String::from("changed")).unwrap();
+
+        let denied = message(vfs.restore(&Vfs::builtin(), "/"));
+        assert_eq!(denied, "Permission denied! Path: '/bin/sl'");
+        assert_eq!(vfs.file("/home/log"), Some("changed"));
+        assert!(vfs.restore(&Vfs::builtin(), "/root/secret").is_err());
+        assert!(vfs.file("/root/secret").is_some());
+        let denied = message(vfs.factory_reset(&Vfs::builtin()));
+        assert_eq!(
+            denied,
+            "Permission denied! Only root can reset the filesystem"
+        );
+
+        vfs.restore(&Vfs::builtin(), "/home").unwrap();
+        assert_eq!(vfs.file("/home/log"), Some(""));
+        as_root(&mut vfs, |vfs| vfs.restore(&Vfs::builtin(), "/"));
+        assert!(vfs.exists("/bin/sl") && !vfs.exists("/root/secret"));
+        as_root(&mut vfs, |vfs| vfs.factory_reset(&Vfs::builtin()));
+        assert_eq!(vfs.user().0, "gc");
+    }
+
+    #[test]
+    fn restore_files_replaced_by_directories() {
+        let mut vfs = Vfs::builtin();
+        as_root(&mut vfs, |vfs| {
+            vfs.remove("/bin/ls", false)?;
+            vfs.mkdir("/bin/ls")?;
+            vfs.write("/bin/ls/inside", String::new())
+        });
+        let denied = message(vfs.restore(&Vfs::builtin(), "/bin/ls"));
+        assert_eq!(denied, "Permission denied! Path: '/bin/ls'");
+        as_root(&mut vfs, |vfs| vfs.restore(&Vfs::builtin(), "/bin/ls"));
+        assert_eq!(vfs.file("/bin/ls"), Vfs::builtin().file("/bin/ls"));
+        assert_eq!(vfs.stat("/bin/ls").unwrap().metadata.mode, 0o755);
+
+        // * The directory in the way has to be removable by the user
+        vfs.remove("/home/log", false).unwrap();
+        vfs.mkdir_all("/home/log/deeper").unwrap();
+        vfs.write("/home/log/deeper/file", String::new()).unwrap();
+        vfs.chmod("/home/log/deeper", 0o500).unwrap();
+        let denied = message(vfs.restore(&Vfs::builtin(), "/home"));
+        assert_eq!(denied, "Permission denied! Path: '/home/log/deeper'");
+        assert!(vfs.exists("/home/log/deeper/file"));
+        vfs.chmod("/home/log/deeper", 0o700).unwrap();
+        vfs.restore(&Vfs::builtin(), "/home").unwrap();
+        assert_eq!(vfs.file("/home/log"), Some(""));
+        assert!(diff(&vfs, "/").is_empty());
+    }
+}
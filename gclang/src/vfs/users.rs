@@ -0,0 +1,75 @@
+//! Accounts, kept in `/etc/passwd`, `/etc/shadow` and `/etc/group` like on Unix.
+//! The files are read as they are, so editing them is how accounts change.
+use super::Vfs;
+use crate::encoding::{hex_encode, sha256};
+use crate::executor::{bail, Result};
+
+/// A line of `/etc/passwd`: `name:x:uid:gid:full name:home:shell`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Account {
+    pub name: String,
+    pub gid: String,
+    pub full_name: String,
+    pub home: String,
+    pub shell: String,
+}
+
+impl Vfs {
+    pub fn account(&self, name: &str) -> Option<Account> {
+        self.file("/etc/passwd")?.lines().find_map(|line| {
+            let fields = line.split(':').collect::<Vec<_>>();
+            match fields[..] {
+                [user, _, _, gid, full_name, home, shell] if user == name => Some(Account {
+                    name: user.to_owned(),
+                    gid: gid.to_owned(),
+                    full_name: full_name.to_owned(),
+                    home: home.to_owned(),
+                    shell: shell.to_owned(),
+                }),
+                _ => None,
+            }
+        })
+    }
+
+    /// The primary group first, then every group listing the user in `/etc/group`.
+    pub fn groups_of(&self, account: &Account) -> Vec<String> {
+        let mut primary = Vec::new();
+        let mut groups = Vec::new();
+        for line in self.file("/etc/group").unwrap_or_default().lines() {
+            let fields = line.split(':').collect::<Vec<_>>();
+            if let [group, _, gid, members] = fields[..] {
+                if gid == account.gid {
+                    primary.push(group.to_owned());
+                } else if members.split(',').any(|member| member == account.name) {
+                    groups.push(group.to_owned());
+                }
+            }
+        }
+        primary.extend(groups);
+        primary
+    }
+
+    /// Checks a password against its SHA-256 in `/etc/shadow`, an empty hash means no password.
+    pub fn verify_password(&self, name: &str, password: &str) -> bool {
+        let Some(shadow) = self.file("/etc/shadow") else {
+            return false;
+        };
+        shadow.lines().any(|line| match line.split_once(':') {
+            Some((user, hash)) if user == name => {
+                hash.is_empty() || hash == hex_encode(&sha256(password.as_bytes()))
+            }
+            _ => false,
+        })
+    }
+
+    /// Becomes the user, the working directory stays where it is.
+    pub fn switch_user(&mut self, name: &str) -> Result<()> {
+        let Some(account) = self.account(name) else {
+            bail!(late effect "User not found! Name: '{}'", name);
+        };
+        let groups = self.groups_of(&account);
+        self.set_user(&account.name, groups);
+        self.set_home_directory(&account.home);
+        Ok(())
+    }
+}
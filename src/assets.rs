@@ -7,7 +7,6 @@ use speedy2d::shape::Rectangle;
 
 pub struct Assets {
     pub font: speedy2d::font::Font,
-    pub logs: Vec<&'static str>,
 
     pub tileset: Atlas,
     pub player: Atlas,
@@ -41,23 +40,6 @@ impl Assets {
         Ok(Self {
             font: speedy2d::font::Font::new(include_bytes!("../Assets/JoystixMonospace.ttf"))
                 .map_err(|err| anyhow!(err.to_string()))?,
-            logs: vec![
-                "Nullptr exception!\n[systemctl] Network service was stopped because of OOM,\nrun \"systemctl restart network\" to restart it.\nUser with name \"Garbage Collector\" logged in.",
-                "*4 log lines skipped*\n[firewall] User \"Garbage Collector\" is not in whitelist.\nConsider adding their name to /firewall/whitelist",
-                "*6 log lines skipped*\n[rsa-service] RSA key leaked: \"SGkh\"!",
-                concat!(
-                    "*7 log lines skipped*\n",
-                    "Dear player!\n",
-                    "Unfortunately, I didn't manage to get this game\n",
-                    "done. I spend too much time making a\n",
-                    "langage for it.\n",
-                    "You can study it by viewing files in /bin\n",
-                    "with edit. You can write something in it using\n",
-                    "\"edit /bin/myprogram\", type some code and\n",
-                    "write \"someprogram\"."
-                ),
-            ],
-
             tileset: load_texture!(Tileset, (16, 16)),
             player: load_texture!(Player, (16, 24)),
             terminal: load_texture!(Terminal),
@@ -158,18 +140,36 @@ pub struct Input {
     pub palette_index: usize,
 
     pub typed_text: String,
-    pub scopes: crate::gclang::Scopes,
+    pub network_service: bool,
+    pub scopes: gclang::Scopes,
+    pub storage: Box<dyn gclang::Storage>,
     pub terminal: Option<Terminal>,
 }
 
+/// Globals that are saved along with the filesystem.
+pub const SAVED_GLOBALS: &[&str] = &["gcsh_history"];
+
+/// Files bound to game state: path, device in the terminal's library and mode.
+pub const DEVICES: &[(&str, &str, u16)] = &[
+    ("/proc/player/max_jumps", "max_jumps", 0o666),
+    ("/sys/level/index", "level_index", 0o444),
+    ("/sys/services/network", "network_service", 0o666),
+];
+
 pub struct Terminal {
-    pub program: crate::gclang::Program,
+    pub program: gclang::Program,
     pub scroll: usize,
+    /// Level files to take away when the terminal is closed.
+    pub overlay: gclang::vfs::AppliedOverlay,
 }
 
 impl Terminal {
-    pub fn new(program: crate::gclang::Program) -> Self {
-        Self { program, scroll: 0 }
+    pub fn new(program: gclang::Program) -> Self {
+        Self {
+            program,
+            scroll: 0,
+            overlay: Default::default(),
+        }
     }
 }
 
@@ -177,6 +177,11 @@ impl Default for Input {
     fn default() -> Self {
         use crate::level::Tile;
 
+        #[cfg(not(target_family = "wasm"))]
+        let mut storage: Box<dyn gclang::Storage> = Box::new(gclang::FileStorage::new("save.ron"));
+        #[cfg(target_family = "wasm")]
+        let mut storage: Box<dyn gclang::Storage> = Box::<gclang::MemoryStorage>::default();
+
         Self {
             wasd: IVec2::ZERO,
             arrows: IVec2::ZERO,
@@ -204,7 +209,42 @@ impl Default for Input {
             palette_index: 0,
 
             typed_text: String::new(),
-            scopes: crate::gclang::Scopes::default(),
+            network_service: false,
+            scopes: {
+                let mut scopes = gclang::Scopes::default();
+                *scopes.vfs_mut() = gclang::Vfs::builtin();
+                #[cfg(not(target_family = "wasm"))]
+                if let Ok(epoch) = std::time::UNIX_EPOCH.elapsed() {
+                    scopes.clock_mut().set_epoch(epoch);
+                }
+                if let Err(error) = scopes.restore(&*storage) {
+                    eprintln!(
+                        "Failed to restore the save: {}",
+                        error.message().unwrap_or_default()
+                    );
+                    // * Keep it for the player to fix, instead of saving over it later
+                    if let Err(error) = storage.set_aside() {
+                        eprintln!("{}", error.message().unwrap_or_default());
+                    }
+                }
+                #[cfg(not(target_family = "wasm"))]
+                for mount in gclang::Mount::from_args(&mut std::env::args().collect()) {
+                    if let Err(error) = scopes.vfs_mut().mount(mount) {
+                        eprintln!("Failed to mount: {}", error.message().unwrap_or_default());
+                    }
+                }
+                for (path, device, mode) in DEVICES {
+                    if let Err(error) = scopes.vfs_mut().bind(path, device, *mode) {
+                        eprintln!(
+                            "Failed to bind '{}': {}",
+                            path,
+                            error.message().unwrap_or_default()
+                        );
+                    }
+                }
+                scopes
+            },
+            storage,
             terminal: None,
         }
     }
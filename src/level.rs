@@ -17,6 +17,8 @@ pub enum Tile {
 
 pub struct Level {
     tilemap: BidiArray<Tile>,
+    /// Files the level's terminals show, applied when one is opened.
+    overlays: Vec<gclang::vfs::Overlay>,
     particles: Vec<Particle>,
 }
 
@@ -66,6 +68,7 @@ impl Level {
             .for_each(|tile| *tile = Tile::Ground);
         Self {
             tilemap,
+            overlays: Vec::new(),
             particles: Vec::new(),
         }
     }
@@ -84,14 +87,12 @@ impl Level {
         self.tilemap.get_mut(pos.x as _, pos.y as _)
     }
 
-    pub fn is_lit(&self, input: &mut Input) -> bool {
-        match input
-            .scopes
-            .get_global_or_insert("network_service", crate::gclang::Value::Bool(false))
-        {
-            crate::gclang::Value::Bool(network_service) => *network_service,
-            _ => false,
-        }
+    pub fn is_lit(&self, input: &Input) -> bool {
+        input.network_service
+    }
+
+    pub fn overlays(&self) -> &[gclang::vfs::Overlay] {
+        &self.overlays
     }
 
     pub fn size(&self) -> UVec2 {
@@ -174,11 +175,11 @@ impl Level {
             }
         }
 
-        let filewall_whitelist = match input.scopes.get_path(vec!["firewall", "whitelist"]) {
-            Some(crate::gclang::Value::String(whitelist)) => whitelist
+        let filewall_whitelist = match input.scopes.get_path("/firewall/whitelist") {
+            Some(whitelist) => whitelist
                 .split('\n')
                 .any(|line| line.eq_ignore_ascii_case("Garbage Collector")),
-            _ => false,
+            None => false,
         };
         for particle in &mut self.particles {
             particle.update(
@@ -199,6 +200,8 @@ impl Level {
 pub struct LevelSave {
     size: (usize, usize),
     tilemap: Vec<Tile>,
+    #[serde(default)]
+    overlays: Vec<gclang::vfs::Overlay>,
 }
 
 impl From<&Level> for LevelSave {
@@ -206,6 +209,7 @@ impl From<&Level> for LevelSave {
         Self {
             size: level.tilemap.size(),
             tilemap: level.tilemap.iter().copied().collect(),
+            overlays: level.overlays.clone(),
         }
     }
 }
@@ -216,6 +220,7 @@ impl From<LevelSave> for Level {
         Self {
             tilemap: BidiArray::from_iterator(save.tilemap.drain(..), save.size.0)
                 .expect("Failed to construct tilemap!"),
+            overlays: save.overlays,
             particles: Vec::new(),
         }
     }
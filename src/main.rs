@@ -1,5 +1,4 @@
 pub mod assets;
-pub mod gclang;
 pub mod level;
 pub mod player;
 use assets::*;
@@ -92,6 +91,25 @@ impl Game {
             _ => (),
         };
     }
+
+    /// Closes the terminal if it's open and saves.
+    fn close_terminal(&mut self) {
+        if let Some(terminal) = self.input.terminal.take() {
+            // * Nothing runs the clock while the terminal is closed, and timers
+            // * of programs that didn't finish would fire when it opens again
+            self.input.scopes.clock_mut().cancel_all();
+            if let Err(error) = self.input.scopes.vfs_mut().revert_overlay(terminal.overlay) {
+                eprintln!(
+                    "Failed to revert the level's files: {}",
+                    error.message().unwrap_or_default()
+                );
+            }
+        }
+        let storage = &mut *self.input.storage;
+        if let Err(error) = self.input.scopes.save(storage, SAVED_GLOBALS) {
+            eprintln!("Failed to save: {}", error.message().unwrap_or_default());
+        }
+    }
 }
 
 impl speedy2d::window::WindowHandler for Game {
@@ -263,13 +281,11 @@ impl speedy2d::window::WindowHandler for Game {
         }
 
         if let Some(terminal) = &mut self.input.terminal {
-            if let Some(log) = self.input.scopes.get_path(vec!["home", "log"]) {
-                *log = gclang::Value::String(assets.logs[self.input.index].to_owned());
-            }
-
-            let mut should_exit = false;
+            let should_exit = std::cell::Cell::new(false);
+            let max_jumps = std::cell::Cell::new(self.player.max_jumps);
+            let network_service = std::cell::Cell::new(self.input.network_service);
             {
-                use gclang::Value;
+                use gclang::{Caller, Value, Variadic};
                 get_screen_buffer(&mut self.input.scopes);
                 let border = UVec2::from(13);
                 let screen_height = 30usize;
@@ -282,80 +298,97 @@ impl speedy2d::window::WindowHandler for Game {
                             .width()) as usize;
 
                 let mut library = gclang::Library::with_std();
-                library_function!(library += print (scopes, args) {
+                library.register("print", |caller: &mut Caller, args: Variadic<Value>| {
                     let output = args
+                        .0
                         .iter()
                         .map(ToString::to_string)
                         .collect::<Vec<_>>()
                         .join(", ");
-                    get_screen_buffer(scopes).push_str(&output);
-                    gclang::Ok(Value::Unit)
+                    get_screen_buffer(caller.scopes).push_str(&output);
+                    gclang::Ok(())
                 });
-                library_function!(library += println (scopes, args) {
+                library.register("println", |caller: &mut Caller, args: Variadic<Value>| {
                     let output = args
+                        .0
                         .iter()
                         .map(ToString::to_string)
                         .collect::<Vec<_>>()
                         .join(", ");
-                    let screen = get_screen_buffer(scopes);
+                    let screen = get_screen_buffer(caller.scopes);
                     screen.push_str(&output);
                     screen.push('\n');
-                    gclang::Ok(Value::Unit)
-                });
-                library_function!(library += input (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "input() was not ment to be used with arguments!");
-                    gclang::Ok(Value::String(self.input.typed_text.clone()))
-                });
-                library_function!(library += delta_time (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "delta_time() was not ment to be used with arguments!");
-                    gclang::Ok(Value::Int((delta_time * 1000.0) as _))
+                    gclang::Ok(())
                 });
-                library_function!(library += level_index (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "level_index() was not ment to be used with arguments!");
-                    gclang::Ok(Value::Int(self.input.index as _))
-                });
-                library_function!(library += arrows_x (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "arrows_x() was not ment to be used with arguments!");
-                    gclang::Ok(Value::Int(self.input.arrows.x))
-                });
-                library_function!(library += arrows_y (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "arrows_x() was not ment to be used with arguments!");
-                    gclang::Ok(Value::Int(self.input.arrows.y))
+                library.register(
+                    "printf",
+                    |caller: &mut Caller, template: String, args: Variadic<Value>| {
+                        let output = gclang::format(&template, &args.0)?;
+                        get_screen_buffer(caller.scopes).push_str(&output);
+                        gclang::Ok(())
+                    },
+                );
+                library.register("input", |_: &mut Caller| {
+                    gclang::Ok(self.input.typed_text.clone())
                 });
-                library_function!(library += screen_width (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "screen_width() was not ment to be used with arguments!");
-                    gclang::Ok(Value::Int(screen_width as _))
+                library.register("delta_time", |_: &mut Caller| {
+                    gclang::Ok((delta_time * 1000.0) as i32)
                 });
-                library_function!(library += screen_height (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "screen_height() was not ment to be used with arguments!");
-                    gclang::Ok(Value::Int(screen_height as _))
+                library.register("level_index", |_: &mut Caller| gclang::Ok(self.input.index));
+                library.register("arrows_x", |_: &mut Caller| gclang::Ok(self.input.arrows.x));
+                library.register("arrows_y", |_: &mut Caller| gclang::Ok(self.input.arrows.y));
+                library.register("screen_width", |_: &mut Caller| gclang::Ok(screen_width));
+                library.register("screen_height", |_: &mut Caller| gclang::Ok(screen_height));
+                library.register("exit", |_: &mut Caller| {
+                    should_exit.set(true);
+                    gclang::Ok(())
                 });
-                library_function!(library += exit (_scopes, args) {
-                    gclang::ensure!(args.is_empty(), "exit() was not ment to be used with arguments!");
-                    should_exit = true;
-                    gclang::Ok(Value::Unit)
+                library.add_writable_device(
+                    "max_jumps",
+                    |_: &mut Caller| gclang::Ok(max_jumps.get().to_string()),
+                    |_: &mut Caller, content: String| {
+                        let Some(jumps) = content.trim().parse().ok() else {
+                            gclang::bail!(late effect "Max jumps have to be a number!");
+                        };
+                        max_jumps.set(jumps);
+                        gclang::Ok(())
+                    },
+                );
+                library.add_device("level_index", |_: &mut Caller| {
+                    gclang::Ok(self.input.index.to_string())
                 });
-                if let Err(error) = terminal.program.eval(&mut self.input.scopes, &mut library) {
-                    if let Some(error) = match error {
-                        gclang::Exception::Error(error) => Some(error.to_string()),
-                        gclang::Exception::Effect(gclang::Effect {
-                            effect, handler, ..
-                        })
-                        | gclang::Exception::EffectUnwind(effect, handler, _) => Some(format!(
-                            "Unhandled effect '{}' (handler '{}')!",
-                            effect, handler
-                        )),
-                        gclang::Exception::Resume(_) => {
-                            Some(String::from("Internal error: Resume lost path!"))
+                library.add_writable_device(
+                    "network_service",
+                    |_: &mut Caller| {
+                        let status = if network_service.get() {
+                            "running"
+                        } else {
+                            "stopped"
+                        };
+                        gclang::Ok(status.to_owned())
+                    },
+                    |_: &mut Caller, content: String| {
+                        match content.trim() {
+                            "running" => network_service.set(true),
+                            "stopped" => network_service.set(false),
+                            _ => gclang::bail!(late effect "Services are 'running' or 'stopped'!"),
                         }
-                        gclang::Exception::Return(_) => None,
-                    } {
+                        gclang::Ok(())
+                    },
+                );
+                let elapsed = std::time::Duration::from_secs_f32(delta_time);
+                let timers = self.input.scopes.advance_clock(&mut library, elapsed);
+                let result = terminal.program.eval(&mut self.input.scopes, &mut library);
+                for error in [timers.err(), result.err()].into_iter().flatten() {
+                    if let Some(error) = error.message() {
                         let screen = get_screen_buffer(&mut self.input.scopes);
                         screen.push_str("\x1bff0000");
                         screen.push_str(&error);
                         screen.push_str("\x18\n");
                     }
                 }
+                self.player.max_jumps = max_jumps.get();
+                self.input.network_service = network_service.get();
 
                 // * Draw terminal
                 let scale = self.size.y as f32 / 1.2 / assets.terminal.size().y as f32;
@@ -434,8 +467,8 @@ impl speedy2d::window::WindowHandler for Game {
                     }
                 }
             }
-            if should_exit {
-                self.input.terminal = None;
+            if should_exit.get() {
+                self.close_terminal();
             }
         }
 
@@ -447,6 +480,13 @@ impl speedy2d::window::WindowHandler for Game {
     }
 }
 
+// * The window has no close event, but the handler is dropped when it's closed
+impl Drop for Game {
+    fn drop(&mut self) {
+        self.close_terminal();
+    }
+}
+
 fn get_screen_buffer(scopes: &mut gclang::Scopes) -> &mut String {
     let screen = scopes.get_global_or_insert(
         "screen_buffer",
@@ -19,17 +19,17 @@ impl Player {
     }
 
     fn collides(&mut self, assets: &Assets, level: &Level, input: &mut Input) -> bool {
-        let filewall_whitelist = match input.scopes.get_path(vec!["firewall", "whitelist"]) {
-            Some(crate::gclang::Value::String(whitelist)) => whitelist
+        let filewall_whitelist = match input.scopes.get_path("/firewall/whitelist") {
+            Some(whitelist) => whitelist
                 .split('\n')
                 .any(|line| line.eq_ignore_ascii_case("Garbage Collector")),
-            _ => false,
+            None => false,
         };
-        let private_key = match input.scopes.get_path(vec!["rsa", "keys"]) {
-            Some(crate::gclang::Value::String(whitelist)) => whitelist
+        let private_key = match input.scopes.get_path("/rsa/keys") {
+            Some(whitelist) => whitelist
                 .split('\n')
                 .any(|line| line.eq_ignore_ascii_case("SGkh")),
-            _ => false,
+            None => false,
         };
 
         let tl = (self.position + 1.0) / assets.tileset.tile_size.into_f32();
@@ -60,7 +60,15 @@ impl Player {
                         }
                         Tile::Terminal => {
                             if input.interact {
-                                input.terminal = Some(Terminal::new(crate::gclang::gcsh()));
+                                let mut terminal = Terminal::new(gclang::gcsh());
+                                match input.scopes.vfs_mut().apply_overlay(level.overlays()) {
+                                    Ok(overlay) => terminal.overlay = overlay,
+                                    Err(error) => eprintln!(
+                                        "Failed to apply the level's files: {}",
+                                        error.message().unwrap_or_default()
+                                    ),
+                                }
+                                input.terminal = Some(terminal);
                                 input.interact = false;
                             }
                         }